@@ -0,0 +1,20 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::{error, io};
+
+use crate::Templaar;
+
+/// The handler of the `completions` sub-command.
+///
+/// Prints the completion script for `shell` to stdout. Names of templates are
+/// not completed.
+///
+/// # Arguments
+///
+/// * `shell` - Shell to generate the completion script for
+pub fn completions(shell: Shell) -> Result<(), Box<dyn error::Error>> {
+    let mut cmd = Templaar::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use serde::Deserialize;
+use std::{error, fs, path::PathBuf, sync::OnceLock};
+
+use crate::{errors::InvalidConfig, utils::global_dir_path};
+
+/// Name of the configuration file in the global templates directory.
+const CONFIG_FILE: &str = "config.toml";
+
+/// User configuration read from `config.toml` in the global templates
+/// directory:
+///
+/// ```text
+/// editor = "vim"
+/// extension = "aar"
+/// default_template_name = "templ"
+/// search_root = "/home/user/projects"
+/// ignore_case = true
+/// normalize_compare = true
+/// wait = true
+/// ```
+///
+/// All settings are optional, command line options and environment variables
+/// take precedence over them.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Editor used when neither `--editor` nor $EDITOR is given
+    pub editor: Option<String>,
+    /// Extension of template files used when TEMPLAAR_EXT is not set
+    pub extension: Option<String>,
+    /// Name of the template created by `new` and used by `take` if no name is
+    /// given
+    pub default_template_name: Option<String>,
+    /// Directory at which the search for local templates stops when
+    /// `--search-root` is not given
+    pub search_root: Option<PathBuf>,
+    /// Match template names case-insensitively when `--ignore-case` is not
+    /// given
+    pub ignore_case: Option<bool>,
+    /// Ignore line endings and trailing whitespace when checking if a file
+    /// created by `take` was changed, unless `--normalize-compare` is given
+    pub normalize_compare: Option<bool>,
+    /// Wait for editors which return immediately when `--wait` is not given
+    pub wait: Option<bool>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Read the configuration file. Must be called before the configuration is
+/// used, a missing file results in the default configuration.
+pub fn init() -> Result<(), Box<dyn error::Error>> {
+    let path = global_dir_path()?.join(CONFIG_FILE);
+    let config = match path.exists() {
+        true => {
+            let contents = fs::read_to_string(&path)?;
+            toml::from_str(&contents).map_err(|e| {
+                let line = match e.span() {
+                    Some(span) => contents[..span.start].matches('\n').count() + 1,
+                    None => 1,
+                };
+                InvalidConfig {
+                    path,
+                    reason: format!("line {line}: {}", e.message()),
+                }
+            })?
+        }
+        false => Config::default(),
+    };
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// Get the configuration (the default one if `init` was not called).
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
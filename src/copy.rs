@@ -0,0 +1,71 @@
+use std::{error, fs};
+
+use crate::{
+    errors::TemplExists,
+    take::{find_templ, no_templ_found},
+    utils::{
+        check_templ_name, copy_tree, edit_file, get_editor, global_dir, local_dir, templ_to_path,
+        verbose,
+    },
+};
+
+/// The handler of the `copy` sub-command.
+///
+/// Creates a new template named `dest` as a copy of the template `source`
+/// (found in the same way as by `take`) and opens it in the editor.
+///
+/// # Arguments
+///
+/// * `source` - Name of the copied template
+/// * `dest` - Name of the new template
+/// * `global` - Create a global template
+/// * `no_edit` - Do not open the new template in the editor
+/// * `editor` - Optional editor to use instead of $EDITOR
+pub fn copy(
+    source: &str,
+    dest: &str,
+    global: bool,
+    no_edit: bool,
+    editor: &Option<String>,
+) -> Result<(), Box<dyn error::Error>> {
+    let editor = match no_edit {
+        true => None,
+        false => Some(get_editor(editor)?),
+    };
+
+    let source = &Some(source.to_string());
+    let templ = find_templ(source, &None)?.ok_or_else(|| no_templ_found(&None))?;
+
+    let templ_dir = match global {
+        true => global_dir()?,
+        false => local_dir()?,
+    };
+    check_templ_name(dest, global)?;
+    let templ_file = templ_dir.join(templ_to_path(dest, global));
+    if templ_file.exists() {
+        return Err(Box::new(TemplExists { path: templ_file }));
+    }
+    // Nested global templates are stored in sub-directories
+    if let Some(parent) = templ_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // The template is copied as it is (including ignored files)
+    match templ.is_dir() {
+        true => copy_tree(&templ, &templ_file)?,
+        false => {
+            verbose!("Copying {} -> {}", templ.display(), templ_file.display());
+            fs::copy(&templ, &templ_file)?;
+        }
+    }
+
+    if let Some(editor) = editor {
+        // The copy is new, so it may be removed if the editor fails
+        let discard = || match templ_file.is_dir() {
+            true => fs::remove_dir_all(&templ_file),
+            false => fs::remove_file(&templ_file),
+        };
+        edit_file(&templ_file, &editor, None, Some(&discard))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use similar::TextDiff;
+use std::{
+    env, error, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::NoTarget,
+    frontmatter::{self, Metadata},
+    placeholders::Placeholders,
+    take::{expand_file, find_templ, no_templ_found, target_name, templ_vars},
+    utils::{files_in_tree, is_ignore_file, KEEP_FILE},
+};
+
+/// The handler of the `diff` sub-command.
+///
+/// Prints a unified diff between the template and the existing target (which
+/// would be created by `take` with the same arguments). Placeholders in the
+/// template are expanded the same way as by `take`, except that the user is
+/// not asked for missing values (their placeholders are left untouched).
+/// Nothing is printed if there are no differences.
+///
+/// For directory templates, each template file is compared with the file of
+/// the same path (with expanded placeholders) in the target directory.
+///
+/// # Arguments
+///
+/// * `name` - Optional name of the target
+/// * `template` - Optional name of the template to use
+pub fn diff(name: &Option<String>, template: &Option<String>) -> Result<(), Box<dyn error::Error>> {
+    let templ = find_templ(template, &None)?.ok_or_else(|| no_templ_found(&None))?;
+    let metadata = match templ.is_file() {
+        true => frontmatter::read(&templ)?.0,
+        false => Metadata::default(),
+    };
+    let target = env::current_dir()?.join(target_name(name, metadata.target.clone(), &templ)?);
+    if !target.exists() {
+        return Err(Box::new(NoTarget { path: target }));
+    }
+    let vars = templ_vars(&templ, &metadata, &target, &[], &None)?;
+    let mut placeholders = Placeholders::new(&vars, true, false, false);
+
+    if templ.is_file() {
+        let contents = expand_file(&templ, &templ, &mut placeholders, false)?;
+        return print_diff(&templ, &contents, &target);
+    }
+    let files: Vec<PathBuf> = files_in_tree(&templ)?
+        .into_iter()
+        .filter(|f| !is_ignore_file(f) && !f.ends_with(KEEP_FILE))
+        .collect();
+    for file in files {
+        // Names with placeholders which cannot be expanded are kept
+        let file_path = file.to_string_lossy();
+        let target_file = placeholders
+            .expand_name(&file_path)?
+            .unwrap_or_else(|| file_path.to_string());
+        let templ_file = templ.join(&file);
+        let contents = expand_file(&templ, &templ_file, &mut placeholders, false)?;
+        print_diff(&templ_file, &contents, &target.join(target_file))?;
+    }
+    Ok(())
+}
+
+/// Print a unified diff between the template file `templ` (with contents
+/// `templ_contents`) and the `target` file. A missing target is compared as
+/// an empty file. Lines are colored if stdout is a terminal.
+pub fn print_diff(
+    templ: &Path,
+    templ_contents: &[u8],
+    target: &Path,
+) -> Result<(), Box<dyn error::Error>> {
+    let target_contents = match target.exists() {
+        true => fs::read(target)?,
+        false => vec![],
+    };
+    if templ_contents == target_contents {
+        return Ok(());
+    }
+
+    match (
+        std::str::from_utf8(templ_contents),
+        std::str::from_utf8(&target_contents),
+    ) {
+        (Ok(old), Ok(new)) => {
+            // A missing final newline is marked by a "\ No newline" line
+            let diff = TextDiff::from_lines(old, new)
+                .unified_diff()
+                .header(&templ.to_string_lossy(), &target.to_string_lossy())
+                .to_string();
+            match io::stdout().is_terminal() {
+                true => print!("{}", colorize(&diff)),
+                false => print!("{diff}"),
+            }
+        }
+        _ => println!(
+            "Binary files {} and {} differ",
+            templ.display(),
+            target.display()
+        ),
+    }
+    Ok(())
+}
+
+/// Color lines of a unified diff using ANSI escape sequences.
+fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let color = if line.starts_with("---") || line.starts_with("+++") {
+                "1"
+            } else if line.starts_with("@@") {
+                "36"
+            } else if line.starts_with('-') {
+                "31"
+            } else if line.starts_with('+') {
+                "32"
+            } else {
+                return format!("{line}\n");
+            };
+            format!("\x1b[{color}m{line}\x1b[0m\n")
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, error, path::Path};
+
+use crate::errors::InvalidTemplate;
+
+/// Render the body of the template at `path` using the template engine
+/// declared in the template metadata.
+///
+/// # Arguments
+///
+/// * `engine` - Name of the engine (currently, only "tera" is supported)
+/// * `path` - Path to the template (used in error messages)
+/// * `body` - Template body (without the frontmatter)
+/// * `line_offset` - Number of lines preceding the body in the template file,
+///   added to the line numbers reported in error messages
+/// * `vars` - Values of template variables, used as the rendering context
+pub fn render(
+    engine: &str,
+    path: &Path,
+    body: &str,
+    line_offset: usize,
+    vars: &HashMap<String, String>,
+) -> Result<String, Box<dyn error::Error>> {
+    let invalid = |reason: String| -> Box<dyn error::Error> {
+        Box::new(InvalidTemplate {
+            templ_path: path.to_path_buf(),
+            reason,
+        })
+    };
+    match engine {
+        "tera" => {
+            render_tera(path, body, vars).map_err(|msg| invalid(offset_lines(&msg, line_offset)))
+        }
+        _ => Err(invalid(format!("unknown template engine '{engine}'"))),
+    }
+}
+
+/// Shift the line numbers in a parser error message by `offset`.
+///
+/// Tera reports the location of syntax errors as a " --> line:column" line
+/// followed by a code snippet with the line numbers in a gutter, e.g.:
+///
+/// ```text
+///  --> 2:8
+///   |
+/// 2 | {% for %}
+///   |        ^---
+///   |
+///   = expected an identifier (must start with a-z)
+/// ```
+///
+/// Since the gutter width depends on the number of digits of the line
+/// numbers, it is re-padded to fit the shifted numbers.
+fn offset_lines(msg: &str, offset: usize) -> String {
+    // The gutter width is given by the indentation of the location line
+    let width = msg
+        .lines()
+        .find_map(|l| l.find("--> ").filter(|&i| l[..i].trim().is_empty()));
+    let width = match width {
+        Some(width) if offset > 0 => width,
+        _ => return msg.to_string(),
+    };
+    let shift = |n: &str| n.parse::<usize>().ok().map(|n| (n + offset).to_string());
+
+    // Split the location and the snippet lines into the shifted line number
+    // (empty if there is none) and the rest, other lines are kept as is
+    let split: Vec<Option<(String, String)>> = msg
+        .lines()
+        .map(|line| {
+            let head = line.get(..width)?.trim();
+            let rest = &line[width..];
+            if let Some(loc) = rest.strip_prefix("--> ").filter(|_| head.is_empty()) {
+                let (n, column) = loc.split_once(':')?;
+                Some((String::new(), format!("--> {}:{column}", shift(n)?)))
+            } else if rest.starts_with(" |") || rest.starts_with(" =") {
+                let n = match head {
+                    "" => String::new(),
+                    n => shift(n)?,
+                };
+                Some((n, rest.to_string()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let new_width = split
+        .iter()
+        .flatten()
+        .map(|(n, _)| n.len())
+        .fold(width, usize::max);
+    msg.lines()
+        .zip(split)
+        .map(|(line, split)| match split {
+            Some((n, rest)) => format!("{n:>new_width$}{rest}"),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render the template using Tera. On error, returns the error message.
+#[cfg(feature = "tera")]
+fn render_tera(path: &Path, body: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut context = tera::Context::new();
+    for (name, value) in vars {
+        context.insert(name, value);
+    }
+    let name = path.to_string_lossy();
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template(&name, body)
+        .and_then(|_| tera.render(&name, &context))
+        .map_err(|e| {
+            // Tera errors are chained, the innermost one is the most specific
+            let mut msg = e.to_string();
+            let mut source = error::Error::source(&e);
+            while let Some(e) = source {
+                msg.push_str(&format!("\n{e}"));
+                source = e.source();
+            }
+            msg
+        })
+}
+
+/// Tera support is not compiled in.
+#[cfg(not(feature = "tera"))]
+fn render_tera(_: &Path, _: &str, _: &HashMap<String, String>) -> Result<String, String> {
+    Err(
+        "the template uses the tera engine but templaar was built without \
+         the 'tera' feature"
+            .to_string(),
+    )
+}
@@ -1,16 +1,71 @@
-use std::{error, fmt, path::PathBuf};
+use std::{error, fmt, path::PathBuf, process};
 
-/// No template was found, print a hint.
+/// No template was found.
+///
+/// # Arguments
+///
+/// * `search_end` - Directory at which the search for local templates ended
+///   (if it did not reach the file system root)
 #[derive(Debug, Clone)]
-pub struct NoTemplateFound;
+pub struct NoTemplateFound {
+    pub search_end: Option<PathBuf>,
+}
 
 impl error::Error for NoTemplateFound {}
 
 impl fmt::Display for NoTemplateFound {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let msg = "No template found in the current or parent directories.\n\
-                   For global templates, specify the template name using the -t option.";
-        write!(f, "{}", msg)
+        match &self.search_end {
+            Some(dir) => write!(
+                f,
+                "No template found in the current or parent directories up to {} \
+                 nor in the global templates directory.\n\
+                 Use --search-root to search further.",
+                dir.to_str().ok_or(fmt::Error)?
+            ),
+            None => write!(
+                f,
+                "No template found in the current or parent directories \
+                 nor in the global templates directory."
+            ),
+        }
+    }
+}
+
+/// A template given by its path does not exist.
+///
+/// # Arguments
+///
+/// * `path` - Path to the template
+#[derive(Debug, Clone)]
+pub struct NoTemplateAtPath {
+    pub path: PathBuf,
+}
+
+impl error::Error for NoTemplateAtPath {}
+
+impl fmt::Display for NoTemplateAtPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Template {} does not exist",
+            self.path.to_str().ok_or(fmt::Error)?
+        )
+    }
+}
+
+/// No editor was specified.
+#[derive(Debug, Clone)]
+pub struct NoEditor;
+
+impl error::Error for NoEditor {}
+
+impl fmt::Display for NoEditor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "No editor specified. Set $EDITOR or use the --editor option."
+        )
     }
 }
 
@@ -108,3 +163,367 @@ impl fmt::Display for InvalidTemplate {
         )
     }
 }
+
+/// A template refers to an undefined environment variable which has no default
+/// value.
+///
+/// # Arguments
+///
+/// * `name` - Name of the undefined variable
+#[derive(Debug, Clone)]
+pub struct UndefinedVariable {
+    pub name: String,
+}
+
+impl error::Error for UndefinedVariable {}
+
+impl fmt::Display for UndefinedVariable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Environment variable {} used in the template is not defined.\n\
+             Define it, pass its value using --var {}=<VALUE>, or use --no-env.",
+            self.name, self.name
+        )
+    }
+}
+
+/// Invalid placeholder in a template
+///
+/// # Arguments
+///
+/// * `placeholder` - The placeholder (without braces)
+/// * `reason` - Reason why the placeholder is invalid (error message)
+#[derive(Debug, Clone)]
+pub struct InvalidPlaceholder {
+    pub placeholder: String,
+    pub reason: String,
+}
+
+impl error::Error for InvalidPlaceholder {}
+
+impl fmt::Display for InvalidPlaceholder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid placeholder {{{{{}}}}}: {}",
+            self.placeholder, self.reason
+        )
+    }
+}
+
+/// Invalid file with template variables
+///
+/// # Arguments
+///
+/// * `path` - Path to the vars file
+/// * `reason` - Reason why the file is invalid (error message)
+#[derive(Debug, Clone)]
+pub struct InvalidVarsFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl error::Error for InvalidVarsFile {}
+
+impl fmt::Display for InvalidVarsFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid vars file {}: {}",
+            self.path.to_str().ok_or(fmt::Error)?,
+            self.reason
+        )
+    }
+}
+
+/// Invalid configuration file
+///
+/// # Arguments
+///
+/// * `path` - Path to the config file
+/// * `reason` - Reason why the file is invalid (error message)
+#[derive(Debug, Clone)]
+pub struct InvalidConfig {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl error::Error for InvalidConfig {}
+
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid config file {}: {}",
+            self.path.to_str().ok_or(fmt::Error)?,
+            self.reason
+        )
+    }
+}
+
+/// No file in a directory template matches the pattern.
+///
+/// # Arguments
+///
+/// * `templ_path` - Path to the template
+/// * `pattern` - Path or glob which was not matched
+#[derive(Debug, Clone)]
+pub struct NoMatchingFile {
+    pub templ_path: PathBuf,
+    pub pattern: String,
+}
+
+impl error::Error for NoMatchingFile {}
+
+impl fmt::Display for NoMatchingFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "No file matching '{}' found in template {}",
+            self.pattern,
+            self.templ_path.to_str().ok_or(fmt::Error)?
+        )
+    }
+}
+
+/// The target of a template does not exist.
+///
+/// # Arguments
+///
+/// * `path` - Path to the target
+#[derive(Debug, Clone)]
+pub struct NoTarget {
+    pub path: PathBuf,
+}
+
+impl error::Error for NoTarget {}
+
+impl fmt::Display for NoTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Target {} does not exist",
+            self.path.to_str().ok_or(fmt::Error)?
+        )
+    }
+}
+
+/// A directory with templates cannot be read.
+///
+/// # Arguments
+///
+/// * `path` - Path to the directory
+/// * `reason` - Reason why the directory cannot be read (error message)
+#[derive(Debug, Clone)]
+pub struct UnreadableDir {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl error::Error for UnreadableDir {}
+
+impl fmt::Display for UnreadableDir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot read directory {}: {}",
+            self.path.to_str().ok_or(fmt::Error)?,
+            self.reason
+        )
+    }
+}
+
+/// A path (e.g. of a template) does not end with a file name.
+///
+/// # Arguments
+///
+/// * `path` - The path
+#[derive(Debug, Clone)]
+pub struct NoFileName {
+    pub path: PathBuf,
+}
+
+impl error::Error for NoFileName {}
+
+impl fmt::Display for NoFileName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Path {} does not end with a file name",
+            self.path.display()
+        )
+    }
+}
+
+/// The target of a template would be created inside the template itself.
+///
+/// # Arguments
+///
+/// * `target` - Path to the target
+/// * `templ_path` - Path to the template
+#[derive(Debug, Clone)]
+pub struct TargetInTemplate {
+    pub target: PathBuf,
+    pub templ_path: PathBuf,
+}
+
+impl error::Error for TargetInTemplate {}
+
+impl fmt::Display for TargetInTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot create {} inside of template {}. \
+             Run templaar outside of the template directory or choose another target.",
+            self.target.display(),
+            self.templ_path.display()
+        )
+    }
+}
+
+/// The target of a template is the template itself (e.g. a link to it).
+///
+/// # Arguments
+///
+/// * `target` - Path to the target
+/// * `templ_path` - Path to the template
+#[derive(Debug, Clone)]
+pub struct TargetIsTemplate {
+    pub target: PathBuf,
+    pub templ_path: PathBuf,
+}
+
+impl error::Error for TargetIsTemplate {}
+
+impl fmt::Display for TargetIsTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot create {} from template {}, they are the same file",
+            self.target.display(),
+            self.templ_path.display()
+        )
+    }
+}
+
+/// A name of a new template is not valid (e.g. it would be stored outside of
+/// the templates directory).
+///
+/// # Arguments
+///
+/// * `name` - The template name
+/// * `reason` - Reason why the name is invalid (error message)
+#[derive(Debug, Clone)]
+pub struct InvalidTemplateName {
+    pub name: String,
+    pub reason: String,
+}
+
+impl error::Error for InvalidTemplateName {}
+
+impl fmt::Display for InvalidTemplateName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid template name '{}': {}", self.name, self.reason)
+    }
+}
+
+/// The user would have to be asked but interactive queries are disabled (by
+/// `--non-interactive` or because stdin is not a terminal).
+///
+/// # Arguments
+///
+/// * `prompt` - The query which could not be asked
+#[derive(Debug, Clone)]
+pub struct NonInteractive {
+    pub prompt: String,
+}
+
+impl error::Error for NonInteractive {}
+
+impl fmt::Display for NonInteractive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot ask \"{}\" when running non-interactively. \
+             Use --yes to assume the default answer.",
+            self.prompt
+        )
+    }
+}
+
+/// The user declined to continue when asked (e.g. to keep a file created from
+/// a template without changes).
+#[derive(Debug, Clone)]
+pub struct Declined;
+
+impl error::Error for Declined {}
+
+impl fmt::Display for Declined {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Declined by the user")
+    }
+}
+
+/// The editor exited with a non-zero status.
+///
+/// # Arguments
+///
+/// * `status` - Exit status of the editor
+#[derive(Debug, Clone)]
+pub struct EditorFailed {
+    pub status: process::ExitStatus,
+}
+
+impl error::Error for EditorFailed {}
+
+impl fmt::Display for EditorFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The editor failed ({})", self.status)
+    }
+}
+
+/// A hook command of a template failed.
+///
+/// # Arguments
+///
+/// * `command` - The hook command (with the target expanded)
+/// * `status` - Exit status of the command
+#[derive(Debug, Clone)]
+pub struct HookFailed {
+    pub command: String,
+    pub status: process::ExitStatus,
+}
+
+impl error::Error for HookFailed {}
+
+impl fmt::Display for HookFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Hook `{}` failed ({})", self.command, self.status)
+    }
+}
+
+/// A copy of a template (e.g. when moving it) does not match the original.
+///
+/// # Arguments
+///
+/// * `src` - Path to the original template (which is kept)
+/// * `dst` - Path to the copy
+#[derive(Debug, Clone)]
+pub struct IncompleteCopy {
+    pub src: PathBuf,
+    pub dst: PathBuf,
+}
+
+impl error::Error for IncompleteCopy {}
+
+impl fmt::Display for IncompleteCopy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Copying {} to {} is incomplete, the original was kept",
+            self.src.display(),
+            self.dst.display()
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Deserialize;
+use std::{collections::HashMap, error, fs, path::Path};
+
+use crate::errors::InvalidTemplate;
+
+/// First line of the frontmatter block.
+const START: &str = "---aar";
+/// Last line of the frontmatter block.
+const END: &str = "---";
+
+/// Template metadata stored in an optional frontmatter block at the top of
+/// a file template:
+///
+/// ```text
+/// ---aar
+/// description = "Meeting notes"
+/// target = "notes.md"
+/// hooks = ["git add {target}"]
+///
+/// [vars]
+/// author = "me"
+/// ---
+/// ```
+///
+/// The contents of the block are in the TOML format. The block is stripped
+/// when taking the template.
+#[derive(Debug, Default, Deserialize)]
+pub struct Metadata {
+    /// Description of the template
+    pub description: Option<String>,
+    /// Default name of the target
+    pub target: Option<String>,
+    /// Format of the date used as the target name by `take --dated`
+    pub dated: Option<String>,
+    /// Default values of template variables
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    /// Template engine used to render the template instead of the simple
+    /// placeholder expansion
+    pub engine: Option<String>,
+    /// Shell commands run after the target is created (see `hooks::run`)
+    #[serde(default)]
+    pub hooks: Vec<String>,
+    /// Fail `take` if any of the hooks fails instead of only reporting it
+    #[serde(default)]
+    pub hooks_fatal: bool,
+    /// Number of lines preceding the body (i.e. taken by the frontmatter)
+    #[serde(skip)]
+    pub body_line_offset: usize,
+}
+
+/// Read a file template and split it into the metadata and the body.
+///
+/// Templates without frontmatter (including binary files) have default
+/// metadata and the body is the entire file.
+pub fn read(path: &Path) -> Result<(Metadata, Vec<u8>), Box<dyn error::Error>> {
+    let contents = fs::read(path)?;
+    let text = match std::str::from_utf8(&contents) {
+        Ok(text) => text,
+        Err(_) => return Ok((Metadata::default(), contents)),
+    };
+    match parse(text) {
+        Ok(Some((metadata, body))) => Ok((metadata, body.as_bytes().to_vec())),
+        Ok(None) => Ok((Metadata::default(), contents)),
+        Err(reason) => Err(Box::new(InvalidTemplate {
+            templ_path: path.to_path_buf(),
+            reason,
+        })),
+    }
+}
+
+/// Parse the frontmatter block of `text`.
+///
+/// Returns None if there is no frontmatter, otherwise returns the metadata
+/// and the rest of the text. On error, returns the reason (including the line
+/// number).
+fn parse(text: &str) -> Result<Option<(Metadata, &str)>, String> {
+    let mut lines = text.split_inclusive('\n');
+    let header_start = match lines.next() {
+        Some(line) if line.trim_end() == START => line.len(),
+        _ => return Ok(None),
+    };
+
+    let mut header_len = 0;
+    for line in lines {
+        if line.trim_end() == END {
+            let header = &text[header_start..header_start + header_len];
+            let body = &text[header_start + header_len + line.len()..];
+            let mut metadata: Metadata = toml::from_str(header).map_err(|e| {
+                // The header starts at line 2
+                let line = match e.span() {
+                    Some(span) => header[..span.start].matches('\n').count() + 2,
+                    None => 2,
+                };
+                format!("malformed frontmatter at line {line}: {}", e.message())
+            })?;
+            metadata.body_line_offset = text[..text.len() - body.len()].matches('\n').count();
+            return Ok(Some((metadata, body)));
+        }
+        header_len += line.len();
+    }
+    Err(format!(
+        "frontmatter starting at line 1 is missing the closing '{END}'"
+    ))
+}
@@ -0,0 +1,109 @@
+use std::{
+    error,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::utils::{assume_yes, is_interactive, notice, user_prompt_bool};
+
+/// Status of a file in a git repository.
+#[derive(Debug, PartialEq)]
+enum Status {
+    /// Not tracked and not ignored
+    Untracked,
+    /// Ignored by .gitignore
+    Ignored,
+    /// Tracked without changes
+    Tracked,
+    /// Tracked with uncommitted changes
+    Modified,
+}
+
+/// Run git with `args` in `dir`. Returns None if git could not be run or the
+/// directory is not inside a git repository, otherwise returns whether the
+/// command succeeded and its output.
+fn git(dir: &Path, args: &[&str]) -> Option<(bool, String)> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stderr(process::Stdio::null())
+        .output()
+        .ok()?;
+    // Git exits with 128 if the directory is not a repository
+    if output.status.code() == Some(128) {
+        return None;
+    }
+    Some((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+    ))
+}
+
+/// Get the git status of `path`. Returns None if git is not available or the
+/// path is not inside a git repository.
+fn status(path: &Path) -> Option<Status> {
+    // Run git from the nearest existing directory
+    let dir = path.ancestors().skip(1).find(|d| d.is_dir())?;
+    let path = path.to_str()?;
+
+    let (ignored, _) = git(dir, &["check-ignore", "-q", "--", path])?;
+    if ignored {
+        return Some(Status::Ignored);
+    }
+    let (tracked, _) = git(dir, &["ls-files", "--error-unmatch", "--", path])?;
+    if !tracked {
+        return Some(Status::Untracked);
+    }
+    let (_, changes) = git(dir, &["status", "--porcelain", "--", path])?;
+    match changes.is_empty() {
+        true => Some(Status::Tracked),
+        false => Some(Status::Modified),
+    }
+}
+
+/// Warn if taking a template would overwrite files tracked by git or create
+/// a path ignored by git and ask the user for confirmation (declining by
+/// default). The user is not asked (only warned) if `ask` is false, when
+/// running non-interactively, or with `--yes`.
+///
+/// Returns true if there is nothing to warn about or the user confirmed. If
+/// git is not available or the paths are not in a repository, nothing is
+/// checked.
+///
+/// # Arguments
+///
+/// * `target` - Path to the created file or directory
+/// * `overwritten` - Existing files which will be overwritten
+/// * `ask` - Whether to ask for confirmation (false if overwriting was
+///   requested explicitly)
+pub fn confirm(
+    target: &Path,
+    overwritten: &[PathBuf],
+    ask: bool,
+) -> Result<bool, Box<dyn error::Error>> {
+    let mut warnings = vec![];
+    if status(target) == Some(Status::Ignored) {
+        warnings.push(format!("{} is ignored by git", target.display()));
+    }
+    for file in overwritten {
+        match status(file) {
+            Some(Status::Tracked) => warnings.push(format!("{} is tracked by git", file.display())),
+            Some(Status::Modified) => {
+                warnings.push(format!("{} has uncommitted changes in git", file.display()))
+            }
+            _ => {}
+        }
+    }
+
+    if warnings.is_empty() {
+        return Ok(true);
+    }
+    for warning in warnings {
+        notice!("Warning: {warning}");
+    }
+    if !ask || !is_interactive() || assume_yes() {
+        return Ok(true);
+    }
+    user_prompt_bool("Do you wish to continue?", false)
+}
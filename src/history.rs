@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, error, fs,
+    path::{Path, PathBuf},
+};
+
+/// Template last used by `take` in a directory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LastTemplate {
+    /// Name of the template
+    pub name: String,
+    /// Path to the template
+    pub path: PathBuf,
+}
+
+/// Templates last used by `take`, keyed by the working directory.
+type History = BTreeMap<PathBuf, LastTemplate>;
+
+/// Get the path to the history file: `$XDG_STATE_HOME/templaar/history.json`
+/// or `~/.local/state/templaar/history.json` if `XDG_STATE_HOME` is not set.
+fn history_path() -> Result<PathBuf, Box<dyn error::Error>> {
+    let state_dir = match env::var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Ok(state_dir.join("templaar").join("history.json"))
+}
+
+/// Read the history, a missing or an invalid history file is treated as empty.
+fn read() -> History {
+    history_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Write the history file.
+fn write(history: &History) -> Result<(), Box<dyn error::Error>> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(history)?)?;
+    Ok(())
+}
+
+/// Get the template last used in `dir`.
+pub fn last_templ(dir: &Path) -> Option<LastTemplate> {
+    read().remove(dir)
+}
+
+/// Remember `templ` as the template last used in `dir`.
+pub fn remember(dir: &Path, templ: LastTemplate) -> Result<(), Box<dyn error::Error>> {
+    let mut history = read();
+    history.insert(dir.to_path_buf(), templ);
+    write(&history)
+}
+
+/// The handler of `take --forget`.
+///
+/// Forgets the template last used in the current directory.
+pub fn forget() -> Result<(), Box<dyn error::Error>> {
+    let mut history = read();
+    if history.remove(&env::current_dir()?).is_some() {
+        write(&history)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::{error, path::Path, process};
+
+use crate::{errors::HookFailed, utils::verbose};
+
+/// Placeholder in hook commands which is replaced by the path to the target.
+const TARGET: &str = "{target}";
+
+/// Run the `hooks` commands (in a shell) after `target` was created from a
+/// template. `{target}` in the commands is replaced by the (quoted) path to
+/// the target and the commands are run in the directory containing it.
+///
+/// A failure of a hook is reported and the remaining hooks are run, unless
+/// `fatal` is set in which case `HookFailed` is returned.
+pub fn run(hooks: &[String], target: &Path, fatal: bool) -> Result<(), Box<dyn error::Error>> {
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let quoted = shell_words::quote(&target.to_string_lossy()).to_string();
+    for hook in hooks {
+        let command = hook.replace(TARGET, &quoted);
+        verbose!("Running hook {command}");
+        let status = process::Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .current_dir(dir)
+            .status()?;
+        if !status.success() {
+            let err = HookFailed { command, status };
+            match fatal {
+                true => return Err(Box::new(err)),
+                false => eprintln!("Warning: {err}"),
+            }
+        }
+    }
+    Ok(())
+}
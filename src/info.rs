@@ -0,0 +1,55 @@
+use std::error;
+
+use crate::{
+    frontmatter,
+    list::stat,
+    take::{find_templ, no_templ_found},
+    utils::{canonicalize_lossy, files_in_tree, global_dir_path, path_to_templ},
+};
+
+/// The handler of the `info` sub-command.
+///
+/// Prints details about the template which would be used by `take`: its name,
+/// path, scope, kind, size, modification time, and description (from the
+/// metadata). For directory templates, also the list of their files.
+///
+/// # Arguments
+///
+/// * `name` - Name of the template
+pub fn info(name: &str) -> Result<(), Box<dyn error::Error>> {
+    let name = Some(name.to_string());
+    let templ = find_templ(&name, &None)?.ok_or_else(|| no_templ_found(&None))?;
+
+    let global = canonicalize_lossy(&templ)?.starts_with(canonicalize_lossy(&global_dir_path()?)?);
+    let scope = match global {
+        true => "global",
+        false => "local",
+    };
+    let kind = match templ.is_dir() {
+        true => "directory",
+        false => "file",
+    };
+    let (size, modified) = stat(&templ);
+
+    println!("Name:        {}", path_to_templ(&templ, templ.is_dir())?);
+    println!("Path:        {}", templ.display());
+    println!("Scope:       {scope}");
+    println!("Type:        {kind}");
+    println!("Size:        {size} B");
+    println!("Modified:    {modified}");
+    if templ.is_file() {
+        // Templates with unreadable metadata are printed without description
+        if let Ok((metadata, _)) = frontmatter::read(&templ) {
+            if let Some(description) = metadata.description {
+                println!("Description: {description}");
+            }
+        }
+    } else {
+        let files = files_in_tree(&templ)?;
+        println!("Files:");
+        for file in files {
+            println!("  {}", file.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use chrono::{DateTime, Local};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, error, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    frontmatter,
+    utils::{
+        check_templ_dir, files_in_tree, global_dir, path_to_templ, search_dirs, strip_inner_ext,
+        templs_in_dir, templs_in_tree,
+    },
+};
+
+/// Template entry printed by `list`.
+struct Entry {
+    name: String,
+    /// Scope of the template ("local" or "global"), for local templates also
+    /// the directory (relative to the current one) where it was found
+    tag: String,
+    path: PathBuf,
+    /// Description from the template metadata
+    description: Option<String>,
+}
+
+impl Entry {
+    fn new(name: String, path: PathBuf, tag: String) -> Self {
+        // Templates with unreadable metadata are listed without description
+        let description = match path.is_file() {
+            true => frontmatter::read(&path)
+                .ok()
+                .and_then(|(metadata, _)| metadata.description),
+            false => None,
+        };
+        Self {
+            name,
+            tag: format!("[{tag}]"),
+            path,
+            description,
+        }
+    }
+}
+
+/// The handler of the `list` sub-command.
+///
+/// Prints local templates followed by global templates. Local templates are
+/// searched in the same way as in `take`, i.e. in the current directory and
+/// all its parents.
+///
+/// # Arguments
+///
+/// * `paths` - Print absolute paths to the templates
+/// * `names_only` - Print only (deduplicated) names of the templates
+/// * `tree` - Print the templates grouped by scope and sub-directories
+/// * `long` - Print sizes and modification times of the templates
+/// * `show_local` - List local templates
+/// * `show_global` - List global templates
+/// * `from_dir` - List only templates in this directory (instead of local and
+///   global ones)
+pub fn list(
+    paths: bool,
+    names_only: bool,
+    tree: bool,
+    long: bool,
+    show_local: bool,
+    show_global: bool,
+    from_dir: &Option<PathBuf>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut local = vec![];
+    if let Some(dir) = from_dir {
+        check_templ_dir(dir)?;
+        for templ in templs_in_dir(&env::current_dir()?.join(dir))? {
+            local.push(Entry::new(
+                path_to_templ(&templ, templ.is_dir())?,
+                templ,
+                dir.display().to_string(),
+            ));
+        }
+    } else if show_local {
+        for (depth, dir) in search_dirs()?.iter().enumerate() {
+            let tag = match depth {
+                0 => "local".to_string(),
+                _ => format!("local {}", vec![".."; depth].join("/")),
+            };
+            for templ in templs_in_dir(dir)? {
+                local.push(Entry::new(
+                    path_to_templ(&templ, templ.is_dir())?,
+                    templ,
+                    tag.clone(),
+                ));
+            }
+        }
+    }
+
+    let mut global: Vec<Entry> = vec![];
+    if show_global && from_dir.is_none() {
+        global = templs_in_tree(&global_dir()?)?
+            .into_iter()
+            .map(|(name, templ)| {
+                let name = match templ.is_file() {
+                    true => strip_inner_ext(&name).to_string(),
+                    false => name,
+                };
+                Entry::new(name, templ, "global".to_string())
+            })
+            .collect();
+    }
+
+    // Sort by name, the sort is stable so local templates with the same name
+    // stay ordered from the nearest directory
+    local.sort_by(|a, b| a.name.cmp(&b.name));
+    global.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if names_only {
+        print_names(&local, &global);
+    } else if tree {
+        print_tree(&local, &global, paths);
+    } else {
+        print(&local, &global, paths, long);
+    }
+
+    Ok(())
+}
+
+/// Print sorted and deduplicated names of local and global templates, one per
+/// line.
+fn print_names(local: &[Entry], global: &[Entry]) {
+    let names: BTreeSet<&str> = local
+        .iter()
+        .chain(global.iter())
+        .map(|e| e.name.as_str())
+        .collect();
+    for name in names {
+        println!("{name}");
+    }
+}
+
+/// Print local and global templates with aligned columns. Descriptions of
+/// templates are printed in the last column.
+///
+/// # Arguments
+///
+/// * `local` - Local templates
+/// * `global` - Global templates
+/// * `paths` - Print absolute paths to the templates in an extra column
+/// * `long` - Print sizes and modification times of the templates in extra
+///   columns
+fn print(local: &[Entry], global: &[Entry], paths: bool, long: bool) {
+    let all = || local.iter().chain(global.iter());
+    let name_width = all().map(|e| e.name.len()).max().unwrap_or(0);
+    let tag_width = all().map(|e| e.tag.len()).max().unwrap_or(0);
+    let path_width = all()
+        .map(|e| e.path.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    let stats: Vec<(String, String)> = match long {
+        true => all().map(|e| stat(&e.path)).collect(),
+        false => vec![],
+    };
+    let size_width = stats.iter().map(|(s, _)| s.len()).max().unwrap_or(0);
+
+    for (i, entry) in all().enumerate() {
+        let mut line = format!("{:name_width$}  {:tag_width$}", entry.name, entry.tag);
+        if let Some((size, modified)) = stats.get(i) {
+            line.push_str(&format!("  {size:>size_width$}  {modified}"));
+        }
+        if paths {
+            line.push_str(&format!("  {:path_width$}", entry.path.display()));
+        }
+        if let Some(description) = &entry.description {
+            line.push_str(&format!("  {description}"));
+        }
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Print local and global templates grouped by their scope (and directory
+/// for local templates). Nested templates are printed under their
+/// sub-directories.
+///
+/// # Arguments
+///
+/// * `local` - Local templates
+/// * `global` - Global templates
+/// * `paths` - Print absolute paths to the templates
+fn print_tree(local: &[Entry], global: &[Entry], paths: bool) {
+    // Local scopes are ordered from the current directory
+    let scope = |e: &Entry| {
+        e.tag
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string()
+    };
+    let mut groups: BTreeMap<String, Vec<&Entry>> = BTreeMap::new();
+    for entry in local {
+        groups.entry(scope(entry)).or_default().push(entry);
+    }
+    let groups = groups
+        .into_iter()
+        .chain((!global.is_empty()).then(|| ("global".to_string(), global.iter().collect())));
+
+    for (scope, entries) in groups {
+        println!("{scope}");
+        let mut prev_dirs: Vec<&str> = vec![];
+        for entry in entries {
+            let mut components: Vec<&str> = entry.name.split('/').collect();
+            let name = components.pop().unwrap_or_default();
+            // Print sub-directories which differ from the previous template
+            let common = prev_dirs
+                .iter()
+                .zip(&components)
+                .take_while(|(a, b)| a == b)
+                .count();
+            for (depth, dir) in components.iter().enumerate().skip(common) {
+                println!("{}{dir}/", "  ".repeat(depth + 1));
+            }
+            let mut line = format!("{}{name}", "  ".repeat(components.len() + 1));
+            if paths {
+                line.push_str(&format!("  {}", entry.path.display()));
+            }
+            if let Some(description) = &entry.description {
+                line.push_str(&format!("  {description}"));
+            }
+            println!("{line}");
+            prev_dirs = components;
+        }
+    }
+}
+
+/// Get the size (in bytes) and the modification time of the template at
+/// `path` formatted for `list --long`. The size of a directory template is
+/// the total size of its files and its modification time is the latest one
+/// of its files. Values which cannot be read are printed as "?".
+pub fn stat(path: &Path) -> (String, String) {
+    let mut files = match path.is_dir() {
+        true => files_in_tree(path)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|f| path.join(f))
+            .collect(),
+        false => vec![],
+    };
+    // Empty directory templates use their own modification time
+    if files.is_empty() {
+        files.push(path.to_path_buf());
+    }
+    let mut size = 0;
+    let mut modified: Option<SystemTime> = None;
+    for file in files {
+        let Ok(metadata) = fs::symlink_metadata(&file) else {
+            return ("?".to_string(), "?".to_string());
+        };
+        if !metadata.is_dir() {
+            size += metadata.len();
+        }
+        if let Ok(time) = metadata.modified() {
+            modified = modified.max(Some(time));
+        }
+    }
+    let modified = match modified {
+        Some(time) => DateTime::<Local>::from(time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "?".to_string(),
+    };
+    (size.to_string(), modified)
+}
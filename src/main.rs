@@ -1,12 +1,41 @@
+mod completions;
+mod config;
+mod copy;
+mod diff;
+mod engine;
 mod errors;
+mod frontmatter;
+mod git;
+mod history;
+mod hooks;
+mod info;
+mod list;
 mod new;
+mod placeholders;
+mod promote;
 mod take;
 mod utils;
+mod which;
 
 use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use completions::completions;
+use copy::copy;
+use diff::diff;
+use errors::Declined;
+use info::info;
+use list::list;
 use new::new;
+use placeholders::parse_var;
+use promote::promote;
 use std::{path::PathBuf, process};
-use take::take;
+use take::{list_vars, take, TakeOptions};
+use utils::verbose;
+use which::which;
+
+/// Exit code used when the user declines to continue when asked (e.g. to keep
+/// a file created from a template without changes).
+const EXIT_DECLINED: i32 = 3;
 
 #[derive(Debug, Parser)]
 #[clap(
@@ -16,6 +45,54 @@ use take::take;
 struct Templaar {
     #[clap(subcommand)]
     command: Command,
+    /// Editor to use instead of $EDITOR
+    #[clap(long, global = true)]
+    editor: Option<String>,
+    /// Wait until the user is done with editors which return immediately
+    /// (e.g. GUI editors)
+    #[clap(long, global = true)]
+    wait: bool,
+    /// Never ask the user, fail instead (e.g. when several templates match).
+    /// Implied if stdin is not a terminal.
+    #[clap(long, global = true, alias = "no-interactive")]
+    non_interactive: bool,
+    /// Answer all questions by their default answers
+    #[clap(long, short, global = true)]
+    yes: bool,
+    /// Match template names exactly (not as prefixes or globs)
+    #[clap(long, global = true)]
+    exact: bool,
+    /// Match template names case-insensitively (if no template has exactly the given name)
+    #[clap(long, global = true)]
+    ignore_case: bool,
+    /// Print the searched directories and copied files to stderr
+    #[clap(long, global = true, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Do not print informational messages
+    #[clap(long, short, global = true)]
+    quiet: bool,
+    /// Print templates in parent directories (and global ones) which are
+    /// shadowed by the used template
+    #[clap(long, global = true)]
+    show_shadowed: bool,
+    /// Do not search for local templates above this directory
+    /// (default: the project root, see --root, or the home directory)
+    #[clap(long, global = true)]
+    search_root: Option<PathBuf>,
+    /// Stop the search for local templates at the first directory containing
+    /// this file or directory (default: .git or .templaar)
+    #[clap(
+        long,
+        global = true,
+        value_name = "MARKER",
+        conflicts_with = "search_root"
+    )]
+    root: Option<String>,
+    /// Look up local templates in this directory (and its parents) instead of
+    /// the current one and create new local templates in it (default:
+    /// $TEMPLAAR_DIR)
+    #[clap(long, global = true, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -28,9 +105,19 @@ enum Command {
         #[clap(long, short)]
         global: bool,
         /// Create the template from file(s).
-        /// In case of multiple files, the template will be a directory.
+        /// In case of multiple files or a directory, the template will be a directory.
         #[clap(long, short, verbatim_doc_comment, num_args(0..))]
         files: Vec<PathBuf>,
+        /// Create a directory template even from a single file (or an empty
+        /// one if no files are given)
+        #[clap(long)]
+        dir: bool,
+        /// Read the template contents from stdin (does not open the editor)
+        #[clap(long, requires = "name", conflicts_with_all = ["files", "dir"])]
+        stdin: bool,
+        /// Do not open the created template in the editor
+        #[clap(long)]
+        no_edit: bool,
     },
     /// Create a file from a template
     Take {
@@ -41,20 +128,188 @@ enum Command {
         /// Use specific template
         #[clap(long, short = 't')]
         template: Option<String>,
+        /// Search for the template only in this directory
+        #[clap(long)]
+        from_dir: Option<PathBuf>,
+        /// Set value of a template variable
+        #[clap(
+            long,
+            visible_alias = "set",
+            value_name = "KEY=VALUE",
+            value_parser = parse_var
+        )]
+        var: Vec<(String, String)>,
+        /// Read values of template variables from a file (TOML, JSON, or YAML)
+        #[clap(long)]
+        vars_file: Option<PathBuf>,
+        #[clap(flatten)]
+        options: Box<TakeOptions>,
+        /// Print variables used in the template and exit
+        #[clap(long)]
+        list_vars: bool,
+        /// Print the variables in the JSON format
+        #[clap(long, requires = "list_vars")]
+        json: bool,
+        /// Forget the template last used in the current directory and exit
+        #[clap(long, conflicts_with = "list_vars")]
+        forget: bool,
+    },
+    /// Print path to the template that would be used by take
+    Which {
+        /// Name of the created file (has no effect, allows to use the same
+        /// arguments as for take)
+        name: Option<String>,
+        /// Use specific template
+        #[clap(long, short = 't')]
+        template: Option<String>,
+    },
+    /// Print details about a template (path, scope, size, files, ...)
+    Info {
+        /// Name of the template
+        name: String,
+    },
+    /// Print differences between a template and a file created from it
+    Diff {
+        /// Name of the file created from the template
+        name: Option<String>,
+        /// Use specific template
+        #[clap(long, short = 't')]
+        template: Option<String>,
+    },
+    /// List available templates
+    List {
+        /// Print paths to the templates
+        #[clap(long, short, conflicts_with = "names_only")]
+        paths: bool,
+        /// Print only names of the templates, one per line
+        #[clap(long)]
+        names_only: bool,
+        /// Print the templates grouped by scope and sub-directories
+        #[clap(long, conflicts_with = "names_only")]
+        tree: bool,
+        /// Print sizes and modification times of the templates (for directory
+        /// templates, the total size and the latest time of their files)
+        #[clap(long, short, conflicts_with_all = ["names_only", "tree"])]
+        long: bool,
+        /// List only local templates
+        #[clap(long, conflicts_with = "global")]
+        local: bool,
+        /// List only global templates
+        #[clap(long)]
+        global: bool,
+        /// List only templates in this directory
+        #[clap(long, conflicts_with_all = ["local", "global"])]
+        from_dir: Option<PathBuf>,
+    },
+    /// Create a template as a copy of another template
+    Copy {
+        /// Name of the template to copy
+        source: String,
+        /// Name of the new template
+        dest: String,
+        /// Make the new template global
+        #[clap(long, short)]
+        global: bool,
+        /// Do not open the new template in the editor
+        #[clap(long)]
+        no_edit: bool,
+    },
+    /// Move a local template from the current directory to the global ones
+    Promote {
+        /// Name of the template
+        name: String,
+        /// Copy a global template to the current directory instead
+        #[clap(long)]
+        demote: bool,
+    },
+    /// Print a shell completion script
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
     },
 }
 
 fn main() {
     let templaar = Templaar::parse();
+    utils::set_interactive(!templaar.non_interactive);
+    utils::set_assume_yes(templaar.yes);
+    utils::set_verbose(templaar.verbose);
+    utils::set_quiet(templaar.quiet);
+    utils::set_exact_names(templaar.exact);
+    utils::set_ignore_case(templaar.ignore_case);
+    utils::set_show_shadowed(templaar.show_shadowed);
+    utils::set_wait(templaar.wait);
+    if let Some(dir) = templaar.search_root {
+        utils::set_search_root(dir);
+    }
+    if let Some(marker) = templaar.root {
+        utils::set_root_marker(marker);
+    }
+    if let Some(dir) = templaar.template_dir {
+        utils::set_templ_dir(dir);
+    }
 
-    if let Err(e) = match templaar.command {
+    if let Err(e) = config::init().and_then(|()| match templaar.command {
         Command::New {
             name,
             global,
             files,
-        } => new(&name, global, &files),
-        Command::Take { name, template } => take(&name, &template),
-    } {
+            dir,
+            stdin,
+            no_edit,
+        } => new(&name, global, &files, dir, stdin, no_edit, &templaar.editor)
+            .map(|templ| verbose!("{templ}")),
+        Command::Take {
+            name,
+            template,
+            from_dir,
+            var,
+            vars_file,
+            options,
+            list_vars: false,
+            forget: false,
+            ..
+        } => take(
+            &name,
+            &template,
+            &from_dir,
+            &var,
+            &vars_file,
+            &options,
+            &templaar.editor,
+        )
+        .map(|taken| taken.iter().for_each(|t| verbose!("{t}"))),
+        Command::Take { forget: true, .. } => history::forget(),
+        Command::Take {
+            template,
+            from_dir,
+            json,
+            ..
+        } => list_vars(&template, &from_dir, json),
+        Command::Which { template, .. } => which(&template),
+        Command::Info { name } => info(&name),
+        Command::Diff { name, template } => diff(&name, &template),
+        Command::List {
+            paths,
+            names_only,
+            tree,
+            long,
+            local,
+            global,
+            from_dir,
+        } => list(paths, names_only, tree, long, !global, !local, &from_dir),
+        Command::Copy {
+            source,
+            dest,
+            global,
+            no_edit,
+        } => copy(&source, &dest, global, no_edit, &templaar.editor),
+        Command::Promote { name, demote } => promote(&name, demote),
+        Command::Completions { shell } => completions(shell),
+    }) {
+        if e.is::<Declined>() {
+            process::exit(EXIT_DECLINED);
+        }
         eprintln!("Error: {e}");
         process::exit(1);
     }
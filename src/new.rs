@@ -1,72 +1,206 @@
 use std::{
-    env, error, fs,
-    io::{self, Write},
-    path::PathBuf,
-    process,
+    error, fmt, fs,
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
 };
 
 use crate::{
-    errors::TemplExists,
-    utils::{global_dir, templ_to_path},
+    config,
+    errors::{Declined, InvalidTemplate, NoFileName, TemplExists},
+    utils::{
+        check_templ_name, copy_as_symlink, copy_dir, copy_symlink, edit_file, file_name,
+        files_in_tree, find_nested_templ, get_editor, global_dir, is_interactive, local_dir,
+        templ_to_path, user_input, user_prompt_bool, user_prompt_keep, verbose,
+    },
 };
 
+/// Result of creating a new template.
+#[derive(Debug)]
+pub struct NewTempl {
+    /// Path to the created template
+    pub path: PathBuf,
+    /// Whether the template is global
+    pub global: bool,
+}
+
+impl fmt::Display for NewTempl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scope = match self.global {
+            true => "global",
+            false => "local",
+        };
+        write!(f, "Created {scope} template {}", self.path.display())
+    }
+}
+
 /// The handler of the `new` sub-command
 ///
 /// # Arguments
 ///
 /// * `name` - Optional name of the template. If not specified, the user is
-///            queried for the name.
+///   queried for the name.
 /// * `global` - Boolean flag whether the template should be created as global
 /// * `files` - List of files to create the template from.
+/// * `dir` - Create a directory template even from a single file (or from no
+///   files)
+/// * `stdin` - Read the template contents from stdin instead of opening the
+///   editor
+/// * `no_edit` - Do not open the created template in the editor
+/// * `editor` - Optional editor to use instead of $EDITOR
+///
+/// Returns the created template.
 pub fn new(
     name: &Option<String>,
     global: bool,
     files: &Vec<PathBuf>,
-) -> Result<(), Box<dyn error::Error>> {
+    dir: bool,
+    stdin: bool,
+    no_edit: bool,
+    editor: &Option<String>,
+) -> Result<NewTempl, Box<dyn error::Error>> {
+    // Fail early if there is no editor to open the template in
+    let editor = match stdin || no_edit {
+        true => None,
+        false => Some(get_editor(editor)?),
+    };
+
     let templ_name = match name {
         Some(n) => n.clone(),
         None => {
             // Read template name from stdin
-            let mut buf = String::new();
-            print!("Enter template name (default 'templ'): ");
-            io::stdout().flush()?;
-            io::stdin().read_line(&mut buf)?;
-
-            match buf.trim() {
-                "" => "templ".to_string(),
-                b => b.to_string(),
+            let default = match &config::get().default_template_name {
+                Some(n) => n.clone(),
+                None => "templ".to_string(),
+            };
+            match user_input(&format!("Enter template name (default '{default}'): "))? {
+                b if b.is_empty() => default,
+                b => b,
             }
         }
     };
 
     let templ_dir = match global {
         true => global_dir()?,
-        false => env::current_dir()?,
+        false => local_dir()?,
     };
+    check_templ_name(&templ_name, global)?;
     let templ_file = templ_dir.join(templ_to_path(&templ_name, global));
 
     if templ_file.exists() {
         return Err(Box::new(TemplExists { path: templ_file }));
     }
+    // Global templates affect all projects, let the user confirm them
+    if global && is_interactive() {
+        let prompt = format!("Create global template {}?", templ_file.display());
+        if !user_prompt_bool(&prompt, true)? {
+            return Err(Box::new(Declined));
+        }
+    }
+    // Nested global templates are stored in sub-directories
+    if global {
+        if let Some(parent) = templ_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    // Error if any of the directories contains a template
+    for f in files.iter().filter(|f| f.is_dir()) {
+        if let Some(path) = find_nested_templ(&files_in_tree(f)?) {
+            return Err(Box::new(InvalidTemplate {
+                templ_path: f.join(path),
+                reason: "templates cannot contain other templates".to_string(),
+            }));
+        }
+    }
+
+    if stdin {
+        let mut contents = vec![];
+        io::stdin().read_to_end(&mut contents)?;
+        fs::write(&templ_file, contents)?;
+    }
 
-    match files.len() {
-        0 => {}
-        1 => {
+    match &files[..] {
+        // Without files, the template is created by the editor (or from stdin)
+        [] if !dir && (stdin || !no_edit) => {}
+        [] if !dir => {
+            verbose!("Creating empty {}", templ_file.display());
+            fs::write(&templ_file, "")?;
+        }
+        [src_dir] if src_dir.is_dir() => {
+            // Single directory -> copy its contents to template
+            copy_dir(src_dir, &templ_file)?;
+        }
+        [file] if !dir => {
             // Single file -> copy it to template
-            fs::copy(&files[0], &templ_file)?;
+            copy_file(file, &templ_file)?;
         }
         _ => {
-            // Multiple files -> make template a directory containing all files
-            // under their original names
+            // Multiple files (or --dir) -> make template a directory
+            // containing all files. Files given by relative paths are stored under these
+            // paths, directories are copied recursively.
             fs::create_dir(&templ_file)?;
             for f in files {
-                fs::copy(f, templ_file.join(f.file_name().unwrap()))?;
+                let path = templ_file.join(entry_path(f)?);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                match f.is_dir() {
+                    true => copy_dir(f, &path)?,
+                    false => copy_file(f, &path)?,
+                }
             }
         }
     };
 
-    let editor = env::var("EDITOR")?;
-    process::Command::new(editor).arg(&templ_file).status()?;
+    if let Some(editor) = editor {
+        // The template is new, so it may be removed if the editor fails
+        let discard = || match templ_file.is_dir() {
+            true => fs::remove_dir_all(&templ_file),
+            false if templ_file.exists() => fs::remove_file(&templ_file),
+            false => Ok(()),
+        };
+        edit_file(&templ_file, &editor, None, Some(&discard))?;
+
+        // Check if a template created from scratch was saved empty and if so,
+        // warn and offer user to delete it
+        if files.is_empty()
+            && templ_file.is_file()
+            && fs::read(&templ_file)?.trim_ascii().is_empty()
+            && !user_prompt_keep("The template is empty. Keep it anyways?")?
+        {
+            fs::remove_file(&templ_file)?;
+            return Err(Box::new(Declined));
+        }
+    }
 
-    Ok(())
+    Ok(NewTempl {
+        path: templ_file,
+        global,
+    })
+}
+
+/// Copy the source `file` to `dst`. Symbolic links are recreated the same way
+/// as inside of copied directories (see `copy_dir`).
+fn copy_file(file: &Path, dst: &Path) -> io::Result<()> {
+    verbose!("Copying {} -> {}", file.display(), dst.display());
+    match copy_as_symlink(file, false) {
+        true => copy_symlink(file, dst),
+        false => fs::copy(file, dst).map(|_| ()),
+    }
+}
+
+/// Get the path under which `file` is stored in a directory template.
+/// Relative paths are kept (without `.` and `..` components), otherwise only
+/// the file name is used.
+fn entry_path(file: &Path) -> Result<PathBuf, NoFileName> {
+    if file.is_relative() && !file.components().any(|c| c == Component::ParentDir) {
+        let path: PathBuf = file
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        if !path.as_os_str().is_empty() {
+            return Ok(path);
+        }
+    }
+    Ok(PathBuf::from(file_name(file)?))
 }
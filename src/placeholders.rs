@@ -0,0 +1,397 @@
+use chrono::{Local, NaiveDateTime};
+use std::{
+    collections::HashMap,
+    env, error,
+    fmt::Write as _,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::{InvalidPlaceholder, InvalidVarsFile, UndefinedVariable},
+    utils::user_input_line,
+};
+
+/// Default format of the `{{date}}` placeholder.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Parse a `KEY=VALUE` pair passed via the `--var` option.
+pub fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid variable '{s}', expected KEY=VALUE")),
+    }
+}
+
+/// Read variables from a vars file containing a flat key/value table.
+///
+/// The format is chosen by the file extension: TOML (default), JSON (.json),
+/// or YAML (.yaml or .yml). Numbers and booleans are converted to strings, null
+/// values to empty strings.
+pub fn read_vars_file(path: &Path) -> Result<Vec<(String, String)>, Box<dyn error::Error>> {
+    let invalid = |reason: String| InvalidVarsFile {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let contents = fs::read_to_string(path)?;
+
+    let mut vars = vec![];
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => {
+            let table: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+            for (key, value) in table {
+                let value = match value {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::Bool(_) | serde_json::Value::Number(_) => value.to_string(),
+                    _ => {
+                        return Err(Box::new(invalid(format!(
+                            "value of '{key}' is not a scalar"
+                        ))))
+                    }
+                };
+                vars.push((key, value));
+            }
+        }
+        Some("yaml" | "yml") => {
+            let table: serde_yaml::Mapping =
+                serde_yaml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+            for (key, value) in table {
+                let key = match key {
+                    serde_yaml::Value::String(s) => s,
+                    _ => return Err(Box::new(invalid(format!("key {key:?} is not a string")))),
+                };
+                let value = match value {
+                    serde_yaml::Value::String(s) => s,
+                    serde_yaml::Value::Null => String::new(),
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    _ => {
+                        return Err(Box::new(invalid(format!(
+                            "value of '{key}' is not a scalar"
+                        ))))
+                    }
+                };
+                vars.push((key, value));
+            }
+        }
+        _ => {
+            let table: toml::Table = contents
+                .parse()
+                .map_err(|e: toml::de::Error| invalid(e.to_string()))?;
+            for (key, value) in table {
+                let value = match value {
+                    toml::Value::String(s) => s,
+                    toml::Value::Array(_) | toml::Value::Table(_) => {
+                        return Err(Box::new(invalid(format!(
+                            "value of '{key}' is not a scalar"
+                        ))))
+                    }
+                    _ => value.to_string(),
+                };
+                vars.push((key, value));
+            }
+        }
+    }
+    Ok(vars)
+}
+
+/// Get the path to the sidecar file with default variable values of the
+/// template `templ`, e.g. `.note.aar.vars` for `.note.aar`.
+pub fn sidecar_path(templ: &Path) -> PathBuf {
+    let mut path = templ.as_os_str().to_owned();
+    path.push(".vars");
+    PathBuf::from(path)
+}
+
+/// Read default variable values of the template `templ` from its sidecar file
+/// (see `sidecar_path`) consisting of `KEY=VALUE` lines. Empty lines and lines
+/// starting with `#` are ignored. If there is no sidecar, no variables are
+/// returned.
+pub fn read_sidecar(templ: &Path) -> Result<Vec<(String, String)>, Box<dyn error::Error>> {
+    let path = sidecar_path(templ);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+    let mut vars = vec![];
+    for (i, line) in fs::read_to_string(&path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        vars.push(parse_var(line).map_err(|reason| InvalidVarsFile {
+            path: path.clone(),
+            reason: format!("line {}: {reason}", i + 1),
+        })?);
+    }
+    Ok(vars)
+}
+
+/// Expands `{{...}}` placeholders in template contents.
+///
+/// Supported placeholders:
+/// - `{{name}}` - value of the variable `name` given by `--var name=...` or
+///   in a vars file. If the variable is not given, the user is queried for its
+///   value.
+/// - `{{name:default}}` - same as above but expands to `default` (which may be
+///   empty or contain colons) if the variable is not given.
+/// - `{{env:NAME}}` - value of the environment variable `NAME`. Explicit
+///   variables take precedence over the environment.
+/// - `{{env:NAME:default}}` - same as above but expands to `default` if `NAME`
+///   is not defined.
+/// - `{{date}}` - current local date in the `%Y-%m-%d` format.
+/// - `{{date:FORMAT}}` - current local date and time in the given strftime
+///   format.
+///
+/// The `env`, `date`, and `cursor` variable names are reserved, however, an explicitly
+/// given `date` variable overrides the current date.
+///
+/// The `{{cursor}}` placeholder is removed and its line is remembered so that
+/// the editor can be opened at that line. Only the first occurrence counts.
+///
+/// A literal `{{` can be written as `\{{`.
+pub struct Placeholders {
+    vars: HashMap<String, String>,
+    env: bool,
+    raw: bool,
+    ask: bool,
+    /// Line of the first `{{cursor}}` in the last expanded contents
+    cursor: Option<usize>,
+}
+
+impl Placeholders {
+    /// Create a new expander.
+    ///
+    /// # Arguments
+    ///
+    /// * `vars` - Explicitly given variables (from a vars file and `--var`),
+    ///   later entries override earlier ones
+    /// * `env` - Whether `{{env:...}}` placeholders should be expanded
+    /// * `raw` - Do not expand anything, contents are left untouched
+    /// * `ask` - Whether values of missing variables are queried from the
+    ///   user, otherwise their placeholders are left untouched
+    pub fn new(vars: &[(String, String)], env: bool, raw: bool, ask: bool) -> Self {
+        Self {
+            vars: vars.iter().cloned().collect(),
+            env,
+            raw,
+            ask,
+            cursor: None,
+        }
+    }
+
+    /// Get the explicitly given variables.
+    pub fn vars(&self) -> &HashMap<String, String> {
+        &self.vars
+    }
+
+    /// Get the line (numbered from 1) of the first `{{cursor}}` placeholder in
+    /// the last expanded contents.
+    pub fn cursor_line(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// Expand all placeholders in `content`.
+    pub fn expand(&mut self, content: &str) -> Result<String, Box<dyn error::Error>> {
+        Ok(self.expand_all(content)?.0)
+    }
+
+    /// Expand all placeholders in a file name. Returns None if some of the
+    /// placeholders were left unexpanded.
+    pub fn expand_name(&mut self, name: &str) -> Result<Option<String>, Box<dyn error::Error>> {
+        let (expanded, complete) = self.expand_all(name)?;
+        Ok(complete.then_some(expanded))
+    }
+
+    /// Expand all placeholders in `content`. Returns the expanded content and
+    /// a flag whether all placeholders were expanded.
+    fn expand_all(&mut self, content: &str) -> Result<(String, bool), Box<dyn error::Error>> {
+        if self.raw {
+            let complete = !scan_all(content).any(|(_, escaped)| !escaped);
+            return Ok((content.to_string(), complete));
+        }
+
+        let mut result = String::with_capacity(content.len());
+        let mut complete = true;
+        let mut rest = content;
+        self.cursor = None;
+        while let Some((range, escaped)) = find_placeholder(rest) {
+            result.push_str(&rest[..range.start]);
+            let placeholder = &rest[range.clone()];
+            if escaped {
+                result.push_str("{{");
+            } else if placeholder[2..placeholder.len() - 2].trim() == "cursor" {
+                if self.cursor.is_none() {
+                    self.cursor = Some(result.matches('\n').count() + 1);
+                }
+            } else {
+                match self.expand_one(placeholder[2..placeholder.len() - 2].trim())? {
+                    Some(value) => result.push_str(&value),
+                    None => {
+                        result.push_str(placeholder);
+                        complete = false;
+                    }
+                }
+            }
+            rest = &rest[range.end..];
+        }
+        result.push_str(rest);
+        Ok((result, complete))
+    }
+
+    /// Expand contents of a template file. Contents which are not valid UTF-8
+    /// are returned unchanged.
+    pub fn expand_bytes(&mut self, content: Vec<u8>) -> Result<Vec<u8>, Box<dyn error::Error>> {
+        if self.raw {
+            return Ok(content);
+        }
+        match String::from_utf8(content) {
+            Ok(s) => Ok(self.expand(&s)?.into_bytes()),
+            Err(e) => Ok(e.into_bytes()),
+        }
+    }
+
+    /// Get the value of a single placeholder (without the braces).
+    /// Returns None if the placeholder should be left untouched.
+    fn expand_one(&mut self, placeholder: &str) -> Result<Option<String>, Box<dyn error::Error>> {
+        if let Some(var) = placeholder.strip_prefix("env:") {
+            if !self.env {
+                return Ok(None);
+            }
+            let (name, default) = split_default(var);
+            if let Some(value) = self.vars.get(name) {
+                return Ok(Some(value.clone()));
+            }
+            return match (env::var(name), default) {
+                (Ok(value), _) => Ok(Some(value)),
+                (Err(_), Some(default)) => Ok(Some(default.to_string())),
+                (Err(_), None) => Err(Box::new(UndefinedVariable {
+                    name: name.to_string(),
+                })),
+            };
+        }
+
+        if placeholder.is_empty() {
+            return Ok(None);
+        }
+
+        let (name, default) = split_default(placeholder);
+        if let Some(value) = self.vars.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        if name == "date" {
+            return Ok(Some(format_date(
+                placeholder,
+                default.unwrap_or(DATE_FORMAT),
+            )?));
+        }
+        if let Some(default) = default {
+            return Ok(Some(default.to_string()));
+        }
+        if !self.ask {
+            return Ok(None);
+        }
+
+        // Read the variable value from stdin and remember it for further use
+        let value = user_input_line(&format!("Enter value of '{name}': "), false)?;
+        self.vars.insert(name.to_string(), value.clone());
+        Ok(Some(value))
+    }
+}
+
+/// Variable used in a template.
+pub struct Variable {
+    /// Name of the variable, environment variables are prefixed by "env:"
+    pub name: String,
+    pub default: Option<String>,
+}
+
+/// Get all variables used in `content` (in the order of their first use).
+pub fn scan(content: &str) -> Vec<Variable> {
+    let mut vars: Vec<Variable> = vec![];
+    for (placeholder, escaped) in scan_all(content) {
+        if escaped {
+            continue;
+        }
+        let placeholder = placeholder[2..placeholder.len() - 2].trim();
+
+        let (name, default) = match placeholder.strip_prefix("env:") {
+            Some(var) => {
+                let (name, default) = split_default(var);
+                (format!("env:{name}"), default)
+            }
+            None => {
+                let (name, default) = split_default(placeholder);
+                (name.to_string(), default)
+            }
+        };
+        if name.is_empty()
+            || name == "date"
+            || name == "cursor"
+            || vars.iter().any(|v| v.name == name)
+        {
+            continue;
+        }
+        vars.push(Variable {
+            name,
+            default: default.map(str::to_string),
+        });
+    }
+    vars
+}
+
+/// Iterate over all placeholders in `content`, yielding them (including braces)
+/// together with a flag whether they are escaped.
+fn scan_all(content: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = content;
+    std::iter::from_fn(move || {
+        let (range, escaped) = find_placeholder(rest)?;
+        let placeholder = &rest[range.clone()];
+        rest = &rest[range.end..];
+        Some((placeholder, escaped))
+    })
+}
+
+/// Find the first placeholder in `s` and return its range (including braces).
+/// The returned flag is true if the found `{{` is escaped by a backslash, the
+/// range then contains only the escaped braces (`\{{`).
+fn find_placeholder(s: &str) -> Option<(Range<usize>, bool)> {
+    let start = s.find("{{")?;
+    if s[..start].ends_with('\\') {
+        return Some((start - 1..start + 2, true));
+    }
+    let len = s[start + 2..].find("}}")?;
+    Some((start..start + len + 4, false))
+}
+
+/// Split a placeholder into the variable name and the optional default value.
+/// The name ends at the first colon, the rest is the default value (or the
+/// format for dates).
+fn split_default(placeholder: &str) -> (&str, Option<&str>) {
+    match placeholder.split_once(':') {
+        Some((name, default)) => (name, Some(default)),
+        None => (placeholder, None),
+    }
+}
+
+/// Get the current local time.
+/// The time can be overridden by setting TEMPLAAR_FAKE_NOW to a time in the
+/// `%Y-%m-%dT%H:%M:%S` format.
+pub fn now() -> Result<NaiveDateTime, Box<dyn error::Error>> {
+    match env::var("TEMPLAAR_FAKE_NOW") {
+        Ok(fake) => Ok(fake.parse()?),
+        Err(_) => Ok(Local::now().naive_local()),
+    }
+}
+
+/// Format the current time using a strftime `format`.
+fn format_date(placeholder: &str, format: &str) -> Result<String, Box<dyn error::Error>> {
+    let mut result = String::new();
+    write!(result, "{}", now()?.format(format)).map_err(|_| InvalidPlaceholder {
+        placeholder: placeholder.to_string(),
+        reason: format!("invalid date format '{format}'"),
+    })?;
+    Ok(result)
+}
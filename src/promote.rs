@@ -0,0 +1,80 @@
+use std::{error, fs, io, path::Path};
+
+use crate::{
+    errors::{IncompleteCopy, NoTemplateAtPath, TemplExists},
+    utils::{
+        check_templ_name, copy_tree, global_dir, local_dir, same_tree, templ_to_path, verbose,
+    },
+};
+
+/// The handler of the `promote` sub-command.
+///
+/// Moves the local template `name` from the current directory to the global
+/// templates directory. With `demote`, copies the global template `name` into
+/// the current directory as a local template instead.
+///
+/// # Arguments
+///
+/// * `name` - Name of the template
+/// * `demote` - Copy a global template to a local one
+pub fn promote(name: &str, demote: bool) -> Result<(), Box<dyn error::Error>> {
+    // Local templates cannot be nested
+    check_templ_name(name, false)?;
+    let local = local_dir()?.join(templ_to_path(name, false));
+    let global = global_dir()?.join(templ_to_path(name, true));
+    let (src, dst) = match demote {
+        true => (global, local),
+        false => (local, global),
+    };
+
+    if !src.exists() {
+        return Err(Box::new(NoTemplateAtPath { path: src }));
+    }
+    if dst.exists() {
+        return Err(Box::new(TemplExists { path: dst }));
+    }
+    // Nested global templates are stored in sub-directories
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match demote {
+        true => copy_templ(&src, &dst),
+        false => move_templ(&src, &dst),
+    }
+}
+
+/// Copy the template `src` to `dst` as it is (including ignored files).
+fn copy_templ(src: &Path, dst: &Path) -> Result<(), Box<dyn error::Error>> {
+    match src.is_dir() {
+        true => copy_tree(src, dst)?,
+        false => {
+            verbose!("Copying {} -> {}", src.display(), dst.display());
+            fs::copy(src, dst)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move the template `src` to `dst`. If it cannot be renamed because `dst` is
+/// on another file system, it is copied and removed. The template is never
+/// removed unless the copy has the same contents.
+fn move_templ(src: &Path, dst: &Path) -> Result<(), Box<dyn error::Error>> {
+    verbose!("Moving {} -> {}", src.display(), dst.display());
+    match fs::rename(src, dst) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        result => return Ok(result?),
+    }
+    copy_templ(src, dst)?;
+    if !same_tree(src, dst)? {
+        return Err(Box::new(IncompleteCopy {
+            src: src.to_path_buf(),
+            dst: dst.to_path_buf(),
+        }));
+    }
+    match src.is_dir() {
+        true => fs::remove_dir_all(src)?,
+        false => fs::remove_file(src)?,
+    }
+    Ok(())
+}
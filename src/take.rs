@@ -1,66 +1,1032 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+use clap::{Args, ValueEnum};
+use globset::{GlobBuilder, GlobMatcher};
 use std::{
     env, error,
     ffi::OsStr,
-    fmt, fs,
-    io::{self, Read},
-    path::{Path, PathBuf},
-    process,
+    fmt::{self, Write as _},
+    fs, io,
+    path::{self, Component, Path, PathBuf},
+    slice,
 };
 
 use crate::{
-    errors::{AmbiguousTemplate, InvalidTemplate, NoTemplateFound, PathExists},
-    utils::{global_dir, path_to_templ, user_prompt_bool},
+    config,
+    diff::print_diff,
+    engine,
+    errors::{
+        AmbiguousTemplate, Declined, InvalidTemplate, NoFileName, NoMatchingFile, NoTemplateAtPath,
+        NoTemplateFound, PathExists, TargetInTemplate, TargetIsTemplate,
+    },
+    frontmatter::{self, Metadata},
+    git,
+    history::{self, LastTemplate},
+    hooks,
+    placeholders::{now, read_sidecar, read_vars_file, scan, Placeholders, Variable, DATE_FORMAT},
+    utils::{
+        assume_yes, canonicalize_lossy, check_templ_dir, copy_as_symlink, copy_symlink, edit_file,
+        exact_names, file_name, files_in_tree, find_nested_templ, get_editor, global_dir_path,
+        ignore_case, info, is_binary, is_ignore_file, is_interactive, is_quiet, is_templ,
+        is_verbose, local_dir, notice, path_to_templ, path_to_templ_full, same_file, search_dirs,
+        show_shadowed, strip_inner_ext, templ_ext, templs_in_tree, text_digest, user_prompt_bool,
+        user_prompt_choice, user_prompt_keep, user_prompt_select, verbose, walk_tree, write_atomic,
+        KEEP_FILE,
+    },
 };
 
 /// Searches for a template file in `dir`.
 /// If `name` is given, looks for the corresponding file,
 /// otherwise looks for any file the the ".aar" extension.
+/// See `NameMatch` for how `name` is matched.
 fn find_templ_in_dir(
     dir: &Path,
     name: &Option<String>,
+    matching: NameMatch,
 ) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
-    let templates: Vec<PathBuf> = fs::read_dir(dir)?
-        .filter_map(|f| match f {
-            Ok(file) => (match name {
-                Some(n) => path_to_templ(&file.path()) == *n,
-                None => file.path().extension() == Some(OsStr::new("aar")),
-            })
-            .then_some(file.path()),
-            Err(_) => None,
-        })
-        .collect();
+    verbose!("Searching for templates in {}", dir.display());
+    let (names, templates): (Vec<String>, Vec<PathBuf>) =
+        templs_matching(dir, name, matching)?.into_iter().unzip();
 
     match &templates[..] {
         [] => Ok(None),
         [f] => Ok(Some(f.clone())),
-        _ => Err(Box::new(AmbiguousTemplate {
-            names: templates.iter().map(path_to_templ).collect(),
-            dir: dir.to_path_buf(),
-        })),
+        _ => {
+            // Prefer the template last used in the current directory if no
+            // name was given
+            if name.is_none() {
+                if let Some(last) = history::last_templ(&env::current_dir()?) {
+                    if templates.contains(&last.path) {
+                        notice!(
+                            "Using template {} last used in this directory (use -t to select another one)",
+                            last.name
+                        );
+                        return Ok(Some(last.path));
+                    }
+                }
+            }
+            // Let the user choose if running interactively (and not assuming
+            // default answers). Cancelling the choice declines taking the
+            // template, if there is no answer at all, the template is
+            // ambiguous.
+            if is_interactive() && !assume_yes() {
+                let scope = match global_dir_path()? == dir {
+                    true => "global",
+                    false => "local",
+                };
+                let items: Vec<(String, String)> = names
+                    .iter()
+                    .zip(&templates)
+                    .map(|(n, t)| (n.clone(), format!("{scope}, {}", t.display())))
+                    .collect();
+                match user_prompt_select("Choose template", &items) {
+                    Ok(Some(i)) => return Ok(Some(templates[i].clone())),
+                    Ok(None) => return Err(Box::new(Declined)),
+                    Err(e) if !e.is::<Declined>() => return Err(e),
+                    Err(_) => {}
+                }
+            }
+            Err(Box::new(AmbiguousTemplate {
+                names,
+                dir: dir.to_path_buf(),
+            }))
+        }
+    }
+}
+
+/// Get all templates in `dir` matching `name` (see `find_templ_in_dir`)
+/// together with their full names.
+///
+/// A name matches a template if it is equal to its full name or to the name
+/// without the inner extension (see `strip_inner_ext`). If some template
+/// matches exactly with its full name, other templates are not returned.
+fn templs_matching(
+    dir: &Path,
+    name: &Option<String>,
+    matching: NameMatch,
+) -> Result<Vec<(String, PathBuf)>, Box<dyn error::Error>> {
+    let mut entries = vec![];
+    for path in fs::read_dir(dir)?.filter_map(|f| f.ok().map(|f| f.path())) {
+        entries.push((path_to_templ_full(&path)?, path));
+    }
+    // Global templates may be nested in sub-directories
+    if global_dir_path()? == dir {
+        entries.extend(
+            templs_in_tree(dir)?
+                .into_iter()
+                .filter(|(n, _)| n.contains('/')),
+        );
+    }
+    entries.retain(|(_, path)| is_templ(path));
+    let Some(n) = name else {
+        return Ok(entries);
+    };
+    if let NameMatch::Exact = matching {
+        if entries.iter().any(|(templ_name, _)| templ_name == n) {
+            entries.retain(|(templ_name, _)| templ_name == n);
+            return Ok(entries);
+        }
+    }
+    entries.retain(|(templ_name, path)| {
+        let names = match path.is_file() {
+            true => vec![templ_name.as_str(), strip_inner_ext(templ_name)],
+            false => vec![templ_name.as_str()],
+        };
+        names.into_iter().any(|templ_name| match matching {
+            NameMatch::Exact => templ_name == n,
+            NameMatch::IgnoreCase => templ_name.to_lowercase() == n.to_lowercase(),
+            NameMatch::Fuzzy => fuzzy_match(templ_name, n),
+        })
+    });
+    Ok(entries)
+}
+
+/// How a template name is matched by `find_templ_in_dir`.
+#[derive(Debug, Clone, Copy)]
+enum NameMatch {
+    /// The name must be equal
+    Exact,
+    /// The name must be equal ignoring case
+    IgnoreCase,
+    /// The name may also be a prefix or a glob matching the template name (see
+    /// `fuzzy_match`)
+    Fuzzy,
+}
+
+/// Check if the template `templ_name` matches `pattern` which is either a
+/// shell-style glob (if it contains any of `*?[`) or a prefix of the name.
+/// Case is ignored if `--ignore-case` is given.
+fn fuzzy_match(templ_name: &str, pattern: &str) -> bool {
+    if pattern.contains(['*', '?', '[']) {
+        if let Ok(glob) = GlobBuilder::new(pattern)
+            .case_insensitive(ignore_case())
+            .build()
+        {
+            return glob.compile_matcher().is_match(templ_name);
+        }
+    }
+    match ignore_case() {
+        true => templ_name
+            .to_lowercase()
+            .starts_with(&pattern.to_lowercase()),
+        false => templ_name.starts_with(pattern),
     }
 }
 
 /// Searches for a template.
 ///
 /// The search starts from the current directory and recursively descends into
-/// the parents. If no template is found, the global templates directory is searched.
-fn find_templ(name: &Option<String>) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
-    let mut dir = env::current_dir()?;
-    loop {
-        match find_templ_in_dir(&dir, name)? {
-            Some(file) => return Ok(Some(dir.join(&file))),
-            None => match dir.parent() {
-                Some(parent) => dir = parent.to_path_buf(),
-                None => break,
-            },
+/// the parents up to the search root (see `search_dirs`). If no template is
+/// found, the global templates directory is searched.
+/// If no name is given, a global template is used only if it is the only one.
+///
+/// If no template has exactly the given `name` (and `--exact` is not given),
+/// the search is repeated with `name` used as a prefix or a glob. The matching
+/// template must be unique.
+///
+/// If `from_dir` is given, only that directory is searched.
+///
+/// If no `name` is given, `default_template_name` from the config is used.
+///
+/// A name in the form `TEMPL:ENTRY` (which does not match any template name)
+/// selects the file `ENTRY` from the directory template `TEMPL`.
+///
+/// A name containing a path separator (outside of `ENTRY`) or ending with the
+/// template extension is a path to the template (relative to the current
+/// directory) and it is used directly if it exists. Otherwise, it is searched
+/// as a name of a nested global template (unless it is clearly a path).
+pub fn find_templ(
+    name: &Option<String>,
+    from_dir: &Option<PathBuf>,
+) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
+    let name = &name
+        .clone()
+        .or_else(|| config::get().default_template_name.clone());
+    if let Some(path) = name.as_deref().filter(|n| is_templ_path(n)) {
+        let templ = env::current_dir()?.join(path);
+        if templ.exists() {
+            verbose!("Found template {}", templ.display());
+            return Ok(Some(templ));
+        }
+        if path.starts_with('.') || Path::new(path).is_absolute() || path.ends_with(&templ_ext()) {
+            return Err(Box::new(NoTemplateAtPath { path: templ }));
+        }
+    }
+    if let Some(templ) = find_templ_named(name, from_dir)? {
+        verbose!("Found template {}", templ.display());
+        return Ok(Some(templ));
+    }
+
+    let Some((templ_name, entry)) = name.as_deref().and_then(|n| n.split_once(':')) else {
+        return Ok(None);
+    };
+    let Some(templ) = find_templ_named(&Some(templ_name.to_string()), from_dir)? else {
+        return Ok(None);
+    };
+    let entry_path = Path::new(entry);
+    if templ.is_dir()
+        && entry_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        && templ.join(entry_path).is_file()
+    {
+        verbose!("Found template {}", templ.join(entry_path).display());
+        return Ok(Some(templ.join(entry_path)));
+    }
+    Err(Box::new(NoMatchingFile {
+        templ_path: templ,
+        pattern: entry.to_string(),
+    }))
+}
+
+/// Check if the template `name` is given by a path, see `find_templ`.
+fn is_templ_path(name: &str) -> bool {
+    let templ_part = name.split(':').next().unwrap_or_default();
+    templ_part.contains(path::MAIN_SEPARATOR)
+        || templ_part.contains('/')
+        || name.ends_with(&format!(".{}", templ_ext()))
+}
+
+/// Searches for a template named `name` (or any template if `name` is None),
+/// see `find_templ`.
+fn find_templ_named(
+    name: &Option<String>,
+    from_dir: &Option<PathBuf>,
+) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
+    let mut passes = vec![NameMatch::Exact];
+    if name.is_some() {
+        if ignore_case() {
+            passes.push(NameMatch::IgnoreCase);
+        }
+        if !exact_names() {
+            passes.push(NameMatch::Fuzzy);
         }
     }
+    for matching in passes {
+        if let Some(templ) = search_templ(name, from_dir, matching)? {
+            return Ok(Some(templ));
+        }
+    }
+    Ok(None)
+}
+
+/// Error returned when no template was found by `find_templ`. Says where the
+/// search for local templates ended unless it reached the file system root.
+pub fn no_templ_found(from_dir: &Option<PathBuf>) -> NoTemplateFound {
+    let search_end = match from_dir {
+        Some(_) => None,
+        None => search_dirs()
+            .ok()
+            .and_then(|dirs| dirs.last().filter(|d| d.parent().is_some()).cloned()),
+    };
+    NoTemplateFound { search_end }
+}
+
+/// Search all template directories for a template matching `name`, see
+/// `find_templ_in_dir`.
+fn search_templ(
+    name: &Option<String>,
+    from_dir: &Option<PathBuf>,
+    matching: NameMatch,
+) -> Result<Option<PathBuf>, Box<dyn error::Error>> {
+    if let Some(dir) = from_dir {
+        check_templ_dir(dir)?;
+        return find_templ_in_dir(&env::current_dir()?.join(dir), name, matching);
+    }
 
-    // Search global directory -> name must be specified
-    if name.is_none() {
+    let global_dir = global_dir_path()?;
+    let dirs = search_dirs()?;
+    for (i, dir) in dirs.iter().enumerate() {
+        if let Some(file) = find_templ_in_dir(dir, name, matching)? {
+            let templ = dir.join(&file);
+            if show_shadowed() {
+                let mut farther = dirs[i + 1..].to_vec();
+                if global_dir.is_dir() {
+                    farther.push(global_dir);
+                }
+                print_shadowed(&templ, &farther, name, matching)?;
+            }
+            return Ok(Some(templ));
+        }
+    }
+
+    // Search global directory, if no name is given, it must contain a single
+    // template
+    if !global_dir.is_dir() {
         return Ok(None);
     }
-    return find_templ_in_dir(&global_dir()?, name);
+    find_templ_in_dir(&global_dir, name, matching)
+}
+
+/// Print a note about templates matching `name` in the directories `farther`
+/// (which are searched after the directory of `templ`) that are shadowed by
+/// `templ`.
+fn print_shadowed(
+    templ: &Path,
+    farther: &[PathBuf],
+    name: &Option<String>,
+    matching: NameMatch,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut shadowed = vec![];
+    for dir in farther {
+        shadowed.extend(templs_matching(dir, name, matching)?);
+    }
+    if shadowed.is_empty() {
+        return Ok(());
+    }
+    eprintln!("Note: template {} shadows:", templ.display());
+    for (templ_name, path) in shadowed {
+        eprintln!("  {templ_name} ({})", path.display());
+    }
+    Ok(())
+}
+
+/// Print a notice if the local template `templ` shadows a template of the same
+/// name in a parent directory or in the global templates directory.
+fn notice_shadowed(templ: &Path) -> Result<(), Box<dyn error::Error>> {
+    let global_dir = global_dir_path()?;
+    let dirs = search_dirs()?;
+    // Templates given by path or found by --from-dir are not in the search path
+    let Some(i) = dirs
+        .iter()
+        .position(|d| Some(d.as_path()) == templ.parent())
+    else {
+        return Ok(());
+    };
+    let mut farther = dirs[i + 1..].to_vec();
+    if global_dir.is_dir() {
+        farther.push(global_dir);
+    }
+    let name = Some(path_to_templ_full(templ)?);
+    for dir in farther {
+        if let Some((_, path)) = templs_matching(&dir, &name, NameMatch::Exact)?.first() {
+            notice!(
+                "Note: using template {} which shadows {}",
+                templ.display(),
+                path.display()
+            );
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The handler of `take --list-vars`.
+///
+/// Prints variables used in the template together with their default values
+/// and names of the template files in which they appear.
+///
+/// # Arguments
+///
+/// * `template` - Optional name of the template to use
+/// * `from_dir` - Optional directory to search the template in
+/// * `json` - Print the variables in the JSON format
+pub fn list_vars(
+    template: &Option<String>,
+    from_dir: &Option<PathBuf>,
+    json: bool,
+) -> Result<(), Box<dyn error::Error>> {
+    let templ = find_templ(template, from_dir)?.ok_or_else(|| no_templ_found(from_dir))?;
+    let files = match templ.is_dir() {
+        true => files_in_tree(&templ)?
+            .iter()
+            .filter(|f| !is_ignore_file(f))
+            .map(|f| templ.join(f))
+            .collect(),
+        false => vec![templ.clone()],
+    };
+
+    // Variables with the names of files in which they appear
+    let mut vars: Vec<(Variable, Vec<String>)> = vec![];
+    for file in &files {
+        let (metadata, body) = match templ.is_file() {
+            true => frontmatter::read(file)?,
+            false => (Metadata::default(), fs::read(file)?),
+        };
+        let contents = match String::from_utf8(body) {
+            Ok(contents) => contents,
+            // Skip binary files
+            Err(_) => continue,
+        };
+        let file_name = match file.strip_prefix(&templ) {
+            Ok(path) if templ.is_dir() => path.to_string_lossy().to_string(),
+            _ => file_name(file)?,
+        };
+        for mut var in scan(&contents) {
+            // Default values declared in the metadata
+            if var.default.is_none() {
+                var.default = metadata.vars.get(&var.name).cloned();
+            }
+            match vars.iter_mut().find(|(v, _)| v.name == var.name) {
+                Some((v, var_files)) => {
+                    if v.default.is_none() {
+                        v.default = var.default;
+                    }
+                    var_files.push(file_name.clone());
+                }
+                None => vars.push((var, vec![file_name.clone()])),
+            }
+        }
+    }
+    // Values from the sidecar file are used instead of the defaults
+    for (name, value) in read_sidecar(&templ)? {
+        if let Some((var, _)) = vars.iter_mut().find(|(v, _)| v.name == name) {
+            var.default = Some(value);
+        }
+    }
+
+    if json {
+        let vars: Vec<serde_json::Value> = vars
+            .iter()
+            .map(|(var, files)| {
+                serde_json::json!({
+                    "name": var.name,
+                    "default": var.default,
+                    "files": files,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&vars)?);
+        return Ok(());
+    }
+
+    let defaults: Vec<String> = vars
+        .iter()
+        .map(|(var, _)| match &var.default {
+            Some(d) => format!("(default: {d})"),
+            None => String::new(),
+        })
+        .collect();
+    let name_width = vars.iter().map(|(v, _)| v.name.len()).max().unwrap_or(0);
+    let default_width = defaults.iter().map(|d| d.len()).max().unwrap_or(0);
+    for ((var, files), default) in vars.iter().zip(defaults) {
+        let line = format!(
+            "{:name_width$}  {default:default_width$}  {}",
+            var.name,
+            files.join(", ")
+        );
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Options of the 'take' sub-command affecting how the template is copied.
+#[derive(Args, Clone, Debug)]
+pub struct TakeOptions {
+    /// Do not expand environment variables ({{env:NAME}}) in the template
+    #[clap(long)]
+    pub no_env: bool,
+    /// Do not expand any placeholders in the template
+    #[clap(long)]
+    pub raw: bool,
+    /// Copy files referenced by symbolic links in directory templates instead
+    /// of recreating the links
+    #[clap(long)]
+    pub dereference: bool,
+    /// Do not copy permissions (e.g. the executable bit) of template files
+    #[clap(long)]
+    pub no_preserve_mode: bool,
+    /// Create the target in this directory instead of the current one
+    #[clap(long, short)]
+    pub output_dir: Option<PathBuf>,
+    /// Append a number to the target name (before its extension) which is one
+    /// higher than the numbers of existing targets (e.g. note-2.md)
+    #[clap(long)]
+    pub auto_number: bool,
+    /// Create N numbered copies of the template (e.g. sheet-1 to sheet-N)
+    /// without opening them in the editor
+    #[clap(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "auto_number"
+    )]
+    pub count: Option<u64>,
+    /// Open each of the copies created by --count in the editor
+    #[clap(long, requires = "count", conflicts_with = "no_edit")]
+    pub edit_each: bool,
+    /// Pad the number added by --auto-number or --count with zeros to this
+    /// width
+    #[clap(long, value_name = "N")]
+    pub width: Option<usize>,
+    /// Name the target by the current date in a strftime format (default: the
+    /// `dated` metadata of the template or %Y-%m-%d), keeping the extension of
+    /// the default target name
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        value_parser = parse_date_format,
+        conflicts_with = "name"
+    )]
+    pub dated: Option<String>,
+    /// Copy only the given files (or globs) from a directory template
+    #[clap(long, value_name = "FILE", value_delimiter = ',')]
+    pub only: Vec<String>,
+    /// Copy all files of a directory template directly into the target
+    /// directory, dropping the sub-directories
+    #[clap(long)]
+    pub flatten: bool,
+    /// Copy a file of a directory template under a different name (relative
+    /// to the target directory)
+    #[clap(long, value_name = "FILE=NEW", value_parser = parse_rename)]
+    pub rename: Vec<(PathBuf, PathBuf)>,
+    /// What to do with files which already exist in the target
+    #[clap(long, value_enum, default_value_t = OnConflict::Abort)]
+    pub on_conflict: OnConflict,
+    /// Do not ask for confirmation when overwriting files tracked by git or
+    /// creating files ignored by git
+    #[clap(long)]
+    pub no_git_check: bool,
+    /// Ask what to do with each file which already exists in the target
+    #[clap(long, short, conflicts_with = "on_conflict")]
+    pub interactive: bool,
+    /// Do not open the created file(s) in the editor
+    #[clap(long)]
+    pub no_edit: bool,
+    /// Print a diff between the template and the created file(s) after the
+    /// editor is closed
+    #[clap(long, conflicts_with = "no_edit")]
+    pub diff: bool,
+    /// Ignore differences in line endings (CRLF/LF), trailing whitespace on
+    /// lines, and the final newline when checking if the created file(s) were
+    /// changed in the editor
+    #[clap(long)]
+    pub normalize_compare: bool,
+    /// Do not run the hook commands defined in the template metadata
+    #[clap(long)]
+    pub no_hooks: bool,
+    /// Create an empty file of the given name if no template is found
+    #[clap(long, conflicts_with = "count")]
+    pub scratch: bool,
+}
+
+/// Check a strftime format passed via the `--dated` option.
+fn parse_date_format(s: &str) -> Result<String, String> {
+    match StrftimeItems::new(s).any(|item| item == Item::Error) {
+        true => Err(format!("invalid date format '{s}'")),
+        false => Ok(s.to_string()),
+    }
+}
+
+/// Parse a `FILE=NEW` pair passed via the `--rename` option.
+fn parse_rename(s: &str) -> Result<(PathBuf, PathBuf), String> {
+    match s.split_once('=') {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((PathBuf::from(from), PathBuf::from(to)))
+        }
+        _ => Err(format!("invalid rename '{s}', expected FILE=NEW")),
+    }
+}
+
+/// What to do with template files which already exist in the target.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OnConflict {
+    /// Fail before anything is copied
+    Abort,
+    /// Keep the existing files
+    Skip,
+    /// Replace the existing files
+    Overwrite,
+}
+
+/// Choices offered when a file already exists in the interactive mode.
+const CONFLICT_CHOICES: &[(char, &str)] = &[
+    ('a', "abort"),
+    ('o', "overwrite"),
+    ('s', "skip"),
+    ('O', "overwrite all"),
+    ('S', "skip all"),
+];
+
+/// Check which of the target `files` already exist and decide what to do with
+/// them based on `options`.
+///
+/// In the interactive mode, the user is asked whether to overwrite or skip
+/// each of the existing files or to abort, otherwise the `on_conflict` policy
+/// is used for all of them. Aborting results in an error.
+///
+/// Returns the files which should be skipped and the files which will be
+/// overwritten.
+fn resolve_conflicts(
+    files: &[PathBuf],
+    options: &TakeOptions,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Box<dyn error::Error>> {
+    let mut skipped = vec![];
+    let mut overwritten = vec![];
+    // Policy used for all remaining files
+    let mut all = match options.interactive {
+        true => None,
+        false => Some(options.on_conflict),
+    };
+    for file in files.iter().filter(|f| f.exists()) {
+        let policy = match all {
+            Some(policy) => policy,
+            None => {
+                let prompt = format!("File {} already exists.", file.display());
+                match user_prompt_choice(&prompt, CONFLICT_CHOICES)? {
+                    'o' => OnConflict::Overwrite,
+                    's' => OnConflict::Skip,
+                    'O' => *all.insert(OnConflict::Overwrite),
+                    'S' => *all.insert(OnConflict::Skip),
+                    _ => OnConflict::Abort,
+                }
+            }
+        };
+        match policy {
+            OnConflict::Abort => return Err(Box::new(PathExists { path: file.clone() })),
+            OnConflict::Skip => skipped.push(file.clone()),
+            OnConflict::Overwrite => overwritten.push(file.clone()),
+        }
+    }
+    Ok((skipped, overwritten))
+}
+
+/// Ask the user whether to overwrite the existing `target` file, showing its
+/// size and the time of its last modification. The default answer is "no".
+fn confirm_overwrite(target: &Path) -> Result<bool, Box<dyn error::Error>> {
+    let metadata = fs::metadata(target)?;
+    let modified: DateTime<Local> = metadata.modified()?.into();
+    let prompt = format!(
+        "Target {} exists ({} bytes, modified {}), overwrite?",
+        target.display(),
+        metadata.len(),
+        modified.format("%Y-%m-%d %H:%M")
+    );
+    user_prompt_bool(&prompt, false)
+}
+
+/// Keep only the template `files` and empty `dirs` which match some of the
+/// `patterns`. Patterns are paths relative to the template directory `templ`
+/// and may contain globs, a pattern matching a directory selects all files
+/// inside it.
+///
+/// Returns an error if some of the patterns does not match anything.
+fn select_files(
+    templ: &Path,
+    patterns: &[String],
+    files: &mut Vec<PathBuf>,
+    dirs: &mut Vec<PathBuf>,
+) -> Result<(), Box<dyn error::Error>> {
+    let matchers = patterns
+        .iter()
+        .map(|p| {
+            Ok(GlobBuilder::new(p.trim_end_matches('/'))
+                .literal_separator(true)
+                .build()?
+                .compile_matcher())
+        })
+        .collect::<Result<Vec<GlobMatcher>, globset::Error>>()?;
+
+    // A path is matched if the pattern matches it or any of its parents
+    let matching =
+        |path: &Path, matcher: &GlobMatcher| path.ancestors().any(|p| matcher.is_match(p));
+    for (pattern, matcher) in patterns.iter().zip(&matchers) {
+        if !files
+            .iter()
+            .chain(dirs.iter())
+            .any(|f| matching(f, matcher))
+        {
+            return Err(Box::new(NoMatchingFile {
+                templ_path: templ.to_path_buf(),
+                pattern: pattern.clone(),
+            }));
+        }
+    }
+    files.retain(|f| matchers.iter().any(|m| matching(f, m)));
+    dirs.retain(|d| matchers.iter().any(|m| matching(d, m)));
+    Ok(())
+}
+
+/// Get the name of the target created from the template `templ`. It is either
+/// given explicitly by `name`, by the `target` metadata of the template, or
+/// derived from the template name.
+pub fn target_name(
+    name: &Option<String>,
+    target: Option<String>,
+    templ: &Path,
+) -> Result<String, Box<dyn error::Error>> {
+    Ok(match (name, target) {
+        (Some(n), _) => n.clone(),
+        (None, Some(t)) => t,
+        // Files selected from directory templates keep their names
+        (None, None) => match is_templ(templ) {
+            true => path_to_templ_full(templ)?,
+            false => file_name(templ)?,
+        },
+    })
+}
+
+/// Get the target name for `take --dated`: the current date formatted using
+/// `format` (if empty, using `templ_format` given by the template metadata or
+/// `DATE_FORMAT`) followed by the extension of the `default` target name.
+fn dated_name(
+    format: &str,
+    templ_format: Option<&str>,
+    default: &str,
+    templ: &Path,
+) -> Result<String, Box<dyn error::Error>> {
+    let format = match (format, templ_format) {
+        ("", Some(f)) => f,
+        ("", None) => DATE_FORMAT,
+        (f, _) => f,
+    };
+    let mut name = String::new();
+    write!(name, "{}", now()?.format(format)).map_err(|_| InvalidTemplate {
+        templ_path: templ.to_path_buf(),
+        reason: format!("invalid date format '{format}'"),
+    })?;
+    if let Some(ext) = Path::new(default).extension() {
+        name.push('.');
+        name.push_str(&ext.to_string_lossy());
+    }
+    Ok(name)
+}
+
+/// Split the file name of `path` into the stem with a "-" appended (the
+/// prefix of numbered names) and the extension (including the dot, empty if
+/// there is none).
+fn number_affixes(path: &Path) -> Result<(String, String), NoFileName> {
+    let stem = path.file_stem().ok_or_else(|| NoFileName {
+        path: path.to_path_buf(),
+    })?;
+    let ext = match path.extension() {
+        Some(ext) => format!(".{}", ext.to_string_lossy()),
+        None => String::new(),
+    };
+    Ok((format!("{}-", stem.to_string_lossy()), ext))
+}
+
+/// Number `path` by appending "-N" to its name (before the extension, if there
+/// is one), N is padded with zeros to `width`.
+fn numbered(path: &Path, n: u64, width: usize) -> Result<PathBuf, NoFileName> {
+    let (prefix, ext) = number_affixes(path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    Ok(dir.join(format!("{prefix}{n:0width$}{ext}")))
+}
+
+/// Number the `target` path (see `numbered`) by the number one higher than the
+/// highest number of the existing entries numbered in the same way.
+fn auto_number(target: &Path, width: usize) -> Result<PathBuf, Box<dyn error::Error>> {
+    let (prefix, ext) = number_affixes(target)?;
+    let dir = target.parent().unwrap_or(Path::new("."));
+
+    let mut max = 0;
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            let number = name
+                .strip_prefix(&prefix)
+                .and_then(|n| n.strip_suffix(&ext))
+                .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| n.parse::<u64>().ok());
+            max = max.max(number.unwrap_or(0));
+        }
+    }
+    Ok(numbered(target, max + 1, width)?)
+}
+
+/// Changes made to the target of a template, allowing to restore the original
+/// state of the target if taking the template fails or is declined.
+#[derive(Default)]
+struct Transaction {
+    /// Files and directories created so far, in the order of creation
+    created: Vec<PathBuf>,
+    /// Overwritten files together with their backups
+    backups: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Transaction {
+    /// Create the directory `dir` unless it already exists.
+    fn create_dir(&mut self, dir: &Path) -> io::Result<()> {
+        if !dir.exists() {
+            fs::create_dir(dir)?;
+            self.created.push(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Create the directory `dir` including all its missing parents.
+    fn create_dir_all(&mut self, dir: &Path) -> io::Result<()> {
+        let missing: Vec<&Path> = dir.ancestors().take_while(|d| !d.exists()).collect();
+        for d in missing.into_iter().rev() {
+            self.create_dir(d)?;
+        }
+        Ok(())
+    }
+
+    /// Prepare for creating `file`. If the file already exists, it is moved
+    /// to a backup so that it can be restored.
+    fn create_file(&mut self, file: &Path) -> io::Result<()> {
+        if file.exists() || file.is_symlink() {
+            let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+            let backup = file.with_file_name(format!(".{file_name}.aarbackup"));
+            fs::rename(file, &backup)?;
+            self.backups.push((file.to_path_buf(), backup));
+        }
+        self.created.push(file.to_path_buf());
+        Ok(())
+    }
+
+    /// Finish the transaction by removing the backups of overwritten files
+    /// (unless they were already restored by `discard`).
+    fn commit(self) -> io::Result<()> {
+        for (_, backup) in &self.backups {
+            if backup.symlink_metadata().is_ok() {
+                fs::remove_file(backup)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if any new files or directories were created (not counting the
+    /// overwritten files).
+    fn has_created(&self) -> bool {
+        self.created
+            .iter()
+            .any(|p| !self.backups.iter().any(|(file, _)| file == p))
+    }
+
+    /// Discard the (possibly edited) target: remove the created files and
+    /// directories (see `remove_created`) and restore the overwritten files.
+    fn discard(&self) -> io::Result<()> {
+        remove_created(&self.created)?;
+        for (file, backup) in self.backups.iter().rev() {
+            fs::rename(backup, file)?;
+        }
+        Ok(())
+    }
+
+    /// Remove all created files and directories and restore the overwritten
+    /// files. Errors are ignored so that as much as possible is restored.
+    fn rollback(self) {
+        for path in self.created.iter().rev() {
+            let _ = match path.is_dir() && !path.is_symlink() {
+                true => fs::remove_dir(path),
+                false => fs::remove_file(path),
+            };
+        }
+        for (file, backup) in self.backups.iter().rev() {
+            let _ = fs::rename(backup, file);
+        }
+    }
+}
+
+/// Result of taking a template.
+#[derive(Debug)]
+pub struct Taken {
+    /// Path to the used template (None for an empty file created by
+    /// `--scratch`)
+    pub templ: Option<PathBuf>,
+    /// Path to the created file or directory
+    pub target: PathBuf,
+    /// Files written from the template (without the skipped ones)
+    pub files: Vec<PathBuf>,
+    /// Whether the target was saved (false if it was skipped as it already
+    /// existed)
+    pub saved: bool,
+}
+
+impl fmt::Display for Taken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.saved, &self.templ) {
+            (true, Some(templ)) => write!(
+                f,
+                "Created {} from template {} ({} file(s) written)",
+                self.target.display(),
+                templ.display(),
+                self.files.len()
+            ),
+            (true, None) => write!(f, "Created empty {}", self.target.display()),
+            (false, _) => write!(f, "Skipped existing {}", self.target.display()),
+        }
+    }
+}
+
+/// A file created from a template file.
+struct Copied {
+    /// Path to the template file
+    templ: PathBuf,
+    /// Path to the created file
+    target: PathBuf,
+    /// Length of the expanded contents without trailing whitespace
+    len: u64,
+    /// Digest of the expanded contents (see `text_digest`)
+    digest: u64,
+}
+
+impl Copied {
+    /// Record that `target` was created from `templ` with the given expanded
+    /// `contents` (see `unchanged` for the meaning of `normalize`).
+    fn new(templ: &Path, target: &Path, contents: &[u8], normalize: bool) -> io::Result<Self> {
+        Ok(Copied {
+            templ: templ.to_path_buf(),
+            target: target.to_path_buf(),
+            len: contents.trim_ascii_end().len() as u64,
+            digest: text_digest(contents, normalize)?,
+        })
+    }
+}
+
+/// Check if the created files should be compared after normalization (see
+/// `text_digest`).
+fn normalize_compare(options: &TakeOptions) -> bool {
+    options.normalize_compare || config::get().normalize_compare.unwrap_or(false)
+}
+
+/// Check if none of the `files` was changed from its expanded template
+/// contents (ignoring trailing whitespace). With `normalize`, the contents are
+/// compared after normalization. The files are compared by their digests so
+/// the expanded contents need not be kept in memory.
+fn unchanged(files: &[Copied], normalize: bool) -> io::Result<bool> {
+    for Copied {
+        target,
+        len,
+        digest,
+        ..
+    } in files
+    {
+        if !target.exists() {
+            return Ok(false);
+        }
+        let file = fs::File::open(target)?;
+        if !normalize && file.metadata()?.len() < *len {
+            return Ok(false);
+        }
+        if text_digest(file, normalize)? != *digest {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Function expanding a file of the template again (see `expand_file`).
+type ExpandFile<'a> = dyn FnMut(&Path) -> Result<Vec<u8>, Box<dyn error::Error>> + 'a;
+
+/// Print a diff between the expanded template and each of the `copied` files.
+/// The template files are expanded again by `expand`.
+fn print_diffs(copied: &[Copied], expand: &mut ExpandFile) -> Result<(), Box<dyn error::Error>> {
+    for Copied { templ, target, .. } in copied {
+        print_diff(templ, &expand(templ)?, target)?;
+    }
+    Ok(())
+}
+
+/// Get the values of variables for creating `target` from the template `templ`
+/// (with the given `metadata`). The `name` variable is set to the target name
+/// by default. Later sources of variables override earlier ones: name <
+/// metadata < sidecar file of the template < `vars_file` < `vars` (given by
+/// --var or --set).
+pub fn templ_vars(
+    templ: &Path,
+    metadata: &Metadata,
+    target: &Path,
+    vars: &[(String, String)],
+    vars_file: &Option<PathBuf>,
+) -> Result<Vec<(String, String)>, Box<dyn error::Error>> {
+    let target_file_name = match target.file_name() {
+        Some(n) => n.to_string_lossy().to_string(),
+        None => target.to_string_lossy().to_string(),
+    };
+    let mut all_vars = vec![("name".to_string(), target_file_name)];
+    all_vars.extend(metadata.vars.clone());
+    all_vars.extend(read_sidecar(templ)?);
+    if let Some(file) = vars_file {
+        all_vars.extend(read_vars_file(file)?);
+    }
+    all_vars.extend_from_slice(vars);
+    Ok(all_vars)
+}
+
+/// Expand placeholders in `file` of the template `templ`. For file templates
+/// (`file` is `templ`), the metadata are stripped and the template engine set
+/// by them is used (unless `raw`).
+pub fn expand_file(
+    templ: &Path,
+    file: &Path,
+    placeholders: &mut Placeholders,
+    raw: bool,
+) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    if file != templ {
+        return placeholders.expand_bytes(fs::read(file)?);
+    }
+    let (metadata, body) = frontmatter::read(templ)?;
+    match &metadata.engine {
+        Some(engine) if !raw => Ok(engine::render(
+            engine,
+            templ,
+            &String::from_utf8(body)?,
+            metadata.body_line_offset,
+            placeholders.vars(),
+        )?
+        .into_bytes()),
+        _ => placeholders.expand_bytes(body),
+    }
 }
 
 /// The handler of the 'take' sub-command.
@@ -69,96 +1035,609 @@ fn find_templ(name: &Option<String>) -> Result<Option<PathBuf>, Box<dyn error::E
 ///
 /// * `name` - Optional name of the target
 /// * `template` - Optional name of the template to use
-pub fn take(name: &Option<String>, template: &Option<String>) -> Result<(), Box<dyn error::Error>> {
-    let templ = find_templ(template)?.ok_or(NoTemplateFound)?;
+/// * `from_dir` - Optional directory to search the template in
+/// * `vars` - Values of template variables
+/// * `vars_file` - Optional file with values of template variables, values
+///   from `vars` take precedence
+/// * `options` - Options affecting how the template is copied
+/// * `editor` - Optional editor to use instead of $EDITOR
+///
+/// Returns the result for each created target (several with `--count`).
+pub fn take(
+    name: &Option<String>,
+    template: &Option<String>,
+    from_dir: &Option<PathBuf>,
+    vars: &[(String, String)],
+    vars_file: &Option<PathBuf>,
+    options: &TakeOptions,
+    editor: &Option<String>,
+) -> Result<Vec<Taken>, Box<dyn error::Error>> {
+    let Some(templ) = find_templ(template, from_dir)? else {
+        return match (options.scratch, name) {
+            (true, Some(name)) => Ok(vec![take_scratch(name, options, editor)?]),
+            _ => Err(Box::new(no_templ_found(from_dir))),
+        };
+    };
+    // Say where the template comes from unless it is in the directory in which
+    // the search started
+    if is_verbose() || templ.parent() != Some(local_dir()?.as_path()) {
+        notice!("Using template {}", templ.display());
+    }
+    if !is_quiet() && !show_shadowed() {
+        // The notice is only informational, errors are ignored
+        let _ = notice_shadowed(&templ);
+    }
 
-    let target_name = match name {
-        Some(n) => n.clone(),
-        None => path_to_templ(&templ),
+    let Some(count) = options.count else {
+        return Ok(vec![take_templ(
+            templ, name, vars, vars_file, options, editor,
+        )?]);
     };
-    let target = env::current_dir()?.join(target_name);
+
+    // Create numbered copies of the template, failing before anything is
+    // created if any of them exists
+    let metadata = match templ.is_file() {
+        true => frontmatter::read(&templ)?.0,
+        false => Metadata::default(),
+    };
+    let target = target_path(name, &metadata, &templ, options)?;
+    let width = options.width.unwrap_or(0);
+    let targets = (1..=count)
+        .map(|i| numbered(&target, i, width))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(path) = targets.iter().find(|t| t.exists()) {
+        return Err(Box::new(PathExists { path: path.clone() }));
+    }
+    let copy_options = TakeOptions {
+        dated: None,
+        ..options.clone()
+    };
+    // Values of variables are asked for once and shared by all copies (with
+    // --no-edit, missing variables are left untouched in all copies)
+    let vars = match options.no_edit {
+        true => vars.to_vec(),
+        false => ask_vars(&templ, &metadata, &targets[0], vars, vars_file, options)?,
+    };
+    let mut taken = vec![];
+    for target in &targets {
+        let name = Some(target.to_string_lossy().to_string());
+        taken.push(take_templ(
+            templ.clone(),
+            &name,
+            &vars,
+            vars_file,
+            &copy_options,
+            editor,
+        )?);
+    }
+    let cwd = env::current_dir()?;
+    info!("Created {count} copies:");
+    for Taken { target, .. } in &taken {
+        info!(
+            "  {}",
+            target.strip_prefix(&cwd).unwrap_or(target).display()
+        );
+    }
+    Ok(taken)
+}
+
+/// Ask for values of all variables used by the template `templ` which have no
+/// value yet (see `templ_vars` for the other arguments). Returns `vars`
+/// extended by all known values except for `name` (which differs for each
+/// target).
+fn ask_vars(
+    templ: &Path,
+    metadata: &Metadata,
+    target: &Path,
+    vars: &[(String, String)],
+    vars_file: &Option<PathBuf>,
+    options: &TakeOptions,
+) -> Result<Vec<(String, String)>, Box<dyn error::Error>> {
+    let all_vars = templ_vars(templ, metadata, target, vars, vars_file)?;
+    let mut placeholders = Placeholders::new(&all_vars, !options.no_env, options.raw, true);
+    // Expanding the template asks for the missing values
+    match templ.is_dir() {
+        true => {
+            for file in files_in_tree(templ)? {
+                if is_ignore_file(&file) || file.ends_with(KEEP_FILE) {
+                    continue;
+                }
+                placeholders.expand_name(&file.to_string_lossy())?;
+                expand_file(templ, &templ.join(&file), &mut placeholders, options.raw)?;
+            }
+        }
+        false => {
+            expand_file(templ, templ, &mut placeholders, options.raw)?;
+        }
+    }
+    let mut vars = vars.to_vec();
+    vars.extend(
+        placeholders
+            .vars()
+            .iter()
+            .filter(|(name, _)| *name != "name")
+            .map(|(name, value)| (name.clone(), value.clone())),
+    );
+    Ok(vars)
+}
+
+/// Create an empty file `name` (used with `--scratch` when no template was
+/// found) and open it in the editor.
+fn take_scratch(
+    name: &str,
+    options: &TakeOptions,
+    editor: &Option<String>,
+) -> Result<Taken, Box<dyn error::Error>> {
+    let editor = match options.no_edit {
+        true => None,
+        false => Some(get_editor(editor)?),
+    };
+    let target = match &options.output_dir {
+        Some(dir) => dir.join(name),
+        None => env::current_dir()?.join(name),
+    };
+    if target.exists() {
+        return Err(Box::new(PathExists { path: target }));
+    }
+
+    notice!("No template found, creating an empty file {name}");
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&target, "")?;
+    if let Some(editor) = editor {
+        let discard = || fs::remove_file(&target);
+        edit_file(&target, &editor, None, Some(&discard))?;
+    }
+    Ok(Taken {
+        templ: None,
+        files: vec![target.clone()],
+        target,
+        saved: true,
+    })
+}
+
+/// Get the path of the target created from the template `templ` (see
+/// `target_name`), taking `--dated` and `--output-dir` into account.
+fn target_path(
+    name: &Option<String>,
+    metadata: &Metadata,
+    templ: &Path,
+    options: &TakeOptions,
+) -> Result<PathBuf, Box<dyn error::Error>> {
+    let mut target_name = target_name(name, metadata.target.clone(), templ)?;
+    if let Some(format) = &options.dated {
+        target_name = dated_name(format, metadata.dated.as_deref(), &target_name, templ)?;
+    }
+    Ok(match &options.output_dir {
+        Some(dir) => dir.join(&target_name),
+        None => env::current_dir()?.join(&target_name),
+    })
+}
+
+/// Create the target from the template `templ` (see `take` for the
+/// arguments).
+fn take_templ(
+    templ: PathBuf,
+    name: &Option<String>,
+    vars: &[(String, String)],
+    vars_file: &Option<PathBuf>,
+    options: &TakeOptions,
+    editor: &Option<String>,
+) -> Result<Taken, Box<dyn error::Error>> {
+    // Split a file template into the metadata and the body. Binary files and
+    // copies created by --count (unless --edit-each is given) are not opened
+    // in the editor. Otherwise, fail early if there is no editor to open the
+    // target in.
+    let (metadata, binary) = match templ.is_file() {
+        true => {
+            let (metadata, body) = frontmatter::read(&templ)?;
+            (metadata, is_binary(&body))
+        }
+        false => (Metadata::default(), false),
+    };
+    let no_edit = options.no_edit || (options.count.is_some() && !options.edit_each);
+    let editor = match no_edit || binary {
+        true => None,
+        false => Some(get_editor(editor)?),
+    };
+
+    let target = target_path(name, &metadata, &templ, options)?;
+    let target = match options.auto_number {
+        true => {
+            let target = auto_number(&target, options.width.unwrap_or(0))?;
+            info!("Creating {}", file_name(&target)?);
+            target
+        }
+        false => target,
+    };
+    // Never overwrite the template itself (e.g. via a link) nor create the
+    // target inside of it (e.g. when running take from within a directory
+    // template)
+    if same_file(&target, &templ) {
+        return Err(Box::new(TargetIsTemplate {
+            target,
+            templ_path: templ,
+        }));
+    }
+    if canonicalize_lossy(&target)?.starts_with(templ.canonicalize()?) {
+        return Err(Box::new(TargetInTemplate {
+            target,
+            templ_path: templ,
+        }));
+    }
+
+    let all_vars = templ_vars(&templ, &metadata, &target, vars, vars_file)?;
+    let mut placeholders =
+        Placeholders::new(&all_vars, !options.no_env, options.raw, !options.no_edit);
+
+    // Created files with digests of their expanded template contents
+    let mut copied: Vec<Copied> = vec![];
+    let normalize = normalize_compare(options);
+    // Files and directories created or overwritten, kept until the user
+    // decides whether to keep the target
+    let mut transaction = Transaction::default();
+    // All files written from the template
+    let mut files: Vec<PathBuf> = vec![];
+    // Line where the editor cursor should be placed
+    let mut cursor_line = None;
 
     if templ.is_dir() {
         // Directory template
 
-        let templ_files = templ
-            .read_dir()?
-            .map(|res| res.map(|e| e.path()))
-            .collect::<Result<Vec<_>, io::Error>>()?;
+        // Paths of all template files and empty directories relative to the
+        // template directory
+        let (mut templ_files, mut templ_dirs) = walk_tree(&templ)?;
+        templ_files.retain(|f| !is_ignore_file(f));
+
+        // Directories marked by KEEP_FILE are created even if they contain no
+        // other files, the marker itself is not copied
+        templ_files.retain(|f| match f.file_name() == Some(OsStr::new(KEEP_FILE)) {
+            true => {
+                templ_dirs.extend(
+                    f.parent()
+                        .filter(|p| !p.as_os_str().is_empty())
+                        .map(Path::to_path_buf),
+                );
+                false
+            }
+            false => true,
+        });
 
-        // Error if the template contains sub-directories
-        if templ_files.iter().any(|f| f.is_dir()) {
+        if templ_files.is_empty() && templ_dirs.is_empty() {
             return Err(Box::new(InvalidTemplate {
                 templ_path: templ,
-                reason: "directory template contains sub-directories".to_string(),
+                reason: "directory template is empty".to_string(),
             }));
         }
 
-        // Create the target directory, if it doesn't exist
-        if !target.exists() {
-            fs::create_dir(&target)?;
+        // Error if the template contains another template
+        if let Some(file) = find_nested_templ(&templ_files).or(find_nested_templ(&templ_dirs)) {
+            return Err(Box::new(InvalidTemplate {
+                templ_path: templ.join(file),
+                reason: "templates cannot contain other templates".to_string(),
+            }));
+        }
+
+        // Error if a renamed file is not in the template
+        for (from, _) in &options.rename {
+            if !templ_files.contains(from) {
+                return Err(Box::new(NoMatchingFile {
+                    templ_path: templ,
+                    pattern: from.to_string_lossy().to_string(),
+                }));
+            }
         }
 
-        let target_files = target
-            .read_dir()?
-            .map(|res| res.map(|e| e.path()))
-            .collect::<Result<Vec<_>, io::Error>>()?;
+        // Copy only the selected files
+        if !options.only.is_empty() {
+            select_files(&templ, &options.only, &mut templ_files, &mut templ_dirs)?;
+        }
+
+        // Directories are dropped when flattening
+        if options.flatten {
+            templ_dirs.clear();
+        }
+
+        // Expand placeholders in the directory paths
+        let mut target_dirs: Vec<PathBuf> = vec![];
+        for dir in &templ_dirs {
+            let target_dir = placeholders
+                .expand_name(&dir.to_string_lossy())?
+                .ok_or_else(|| InvalidTemplate {
+                    templ_path: templ.join(dir),
+                    reason: "directory name contains an unexpanded placeholder".to_string(),
+                })?;
+            target_dirs.push(PathBuf::from(target_dir));
+        }
+
+        // Expand placeholders in the file paths
+        let mut target_names: Vec<PathBuf> = vec![];
+        for file in &templ_files {
+            let file_path = file.to_string_lossy();
+            let mut target_name =
+                PathBuf::from(placeholders.expand_name(&file_path)?.ok_or_else(|| {
+                    InvalidTemplate {
+                        templ_path: templ.join(file),
+                        reason: "file name contains an unexpanded placeholder".to_string(),
+                    }
+                })?);
+            if options.flatten {
+                target_name = PathBuf::from(file_name(&target_name)?);
+            }
+            if let Some((_, to)) = options.rename.iter().find(|(from, _)| from == file) {
+                target_name = to.clone();
+            }
+            if let Some(i) = target_names.iter().position(|n| *n == target_name) {
+                return Err(Box::new(InvalidTemplate {
+                    templ_path: templ,
+                    reason: format!(
+                        "files {} and {} are both copied to {}",
+                        templ_files[i].display(),
+                        file.display(),
+                        target_name.display()
+                    ),
+                }));
+            }
+            target_names.push(target_name);
+        }
+
+        // Confirm creating the target directory if its name was not given
+        // explicitly (unless the user cannot be asked or --yes is given)
+        if !target.exists() && name.is_none() && is_interactive() && !assume_yes() {
+            let prompt = format!(
+                "Create directory {} from template {}?",
+                target.to_str().ok_or(fmt::Error)?,
+                path_to_templ(&templ, true)?
+            );
+            if !user_prompt_bool(&prompt, true)? {
+                return Err(Box::new(Declined));
+            }
+        }
 
         // Warn if the target directory is non-empty
-        if !target_files.is_empty() {
+        if target.exists() && target.read_dir()?.next().is_some() {
             let prompt = format!(
                 "Directory {} is not empty, do you wish to continue?",
                 target.to_str().ok_or(fmt::Error)?
             );
-            if !user_prompt_bool(&prompt)? {
-                return Ok(());
+            if !user_prompt_bool(&prompt, true)? {
+                return Err(Box::new(Declined));
+            }
+        }
+
+        // Resolve conflicts with existing files (for the whole tree before
+        // anything is copied)
+        let target_files: Vec<PathBuf> = target_names.iter().map(|n| target.join(n)).collect();
+        for (file, target_file) in templ_files.iter().zip(&target_files) {
+            if same_file(target_file, &templ.join(file)) {
+                return Err(Box::new(TargetIsTemplate {
+                    target: target_file.clone(),
+                    templ_path: templ.join(file),
+                }));
             }
         }
+        let (skipped, overwritten) = resolve_conflicts(&target_files, options)?;
+        let ask = options.on_conflict != OnConflict::Overwrite;
+        if !options.no_git_check && !git::confirm(&target, &overwritten, ask)? {
+            return Err(Box::new(Declined));
+        }
 
-        // Error if the target directory contains any of the template files
-        match target_files.iter().find(|file| {
-            templ_files
-                .iter()
-                .any(|f| file.file_name() == f.file_name())
-        }) {
-            Some(file) => return Err(Box::new(PathExists { path: file.clone() })),
-            None => {}
+        // Expand the template files and copy them to the target directory,
+        // recreating the directory structure. If anything fails, the target
+        // is restored to its original state.
+        let mut copy = || -> Result<(), Box<dyn error::Error>> {
+            transaction.create_dir(&target)?;
+            for dir in &target_dirs {
+                transaction.create_dir_all(&target.join(dir))?;
+            }
+            for (file, target_file) in templ_files.iter().zip(&target_files) {
+                if skipped.contains(target_file) {
+                    continue;
+                }
+                let templ_file = templ.join(file);
+                if let Some(parent) = target_file.parent() {
+                    transaction.create_dir_all(parent)?;
+                }
+                transaction.create_file(target_file)?;
+                verbose!(
+                    "Copying {} -> {}",
+                    templ_file.display(),
+                    target_file.display()
+                );
+                if copy_as_symlink(&templ_file, options.dereference) {
+                    copy_symlink(&templ_file, target_file)?;
+                    continue;
+                }
+                let contents = expand_file(&templ, &templ_file, &mut placeholders, options.raw)?;
+                let mode_of = (!options.no_preserve_mode).then_some(templ_file.as_path());
+                write_atomic(target_file, &contents, mode_of)?;
+                copied.push(Copied::new(&templ_file, target_file, &contents, normalize)?);
+            }
+            Ok(())
+        };
+        if let Err(e) = copy() {
+            transaction.rollback();
+            return Err(e);
         }
+        files = target_files
+            .iter()
+            .filter(|f| !skipped.contains(f))
+            .cloned()
+            .collect();
 
-        // Copy files from the template to the target directory
-        for file in templ_files {
-            fs::copy(&file, target.join(file.file_name().unwrap()))?;
+        // Print a summary if some files already existed
+        if !skipped.is_empty() || !overwritten.is_empty() {
+            info!(
+                "{} copied, {} skipped, {} overwritten",
+                target_files.len() - skipped.len() - overwritten.len(),
+                skipped.len(),
+                overwritten.len()
+            );
+        }
+        if !skipped.is_empty() {
+            info!("Skipped existing files:");
+            for file in &skipped {
+                info!("  {}", file.strip_prefix(&target)?.display());
+            }
         }
     } else {
         // File template
 
-        // Error if the target already exists
-        if target.exists() {
-            return Err(Box::new(PathExists { path: target }));
+        if !options.only.is_empty() || !options.rename.is_empty() {
+            return Err(Box::new(InvalidTemplate {
+                templ_path: templ,
+                reason: "--only and --rename can be used with directory templates only".to_string(),
+            }));
+        }
+
+        // Check if the target already exists. Instead of failing, the user is
+        // asked whether to overwrite it when running in a terminal.
+        let ask = target.exists()
+            && !options.interactive
+            && options.on_conflict == OnConflict::Abort
+            && is_interactive();
+        let (skipped, overwritten) = match ask {
+            true => {
+                if !confirm_overwrite(&target)? {
+                    return Err(Box::new(Declined));
+                }
+                (vec![], vec![target.clone()])
+            }
+            false => resolve_conflicts(slice::from_ref(&target), options)?,
+        };
+        if !skipped.is_empty() {
+            info!("Skipped existing file {}", target.display());
+            return Ok(Taken {
+                templ: Some(templ),
+                target,
+                files,
+                saved: false,
+            });
+        }
+        let ask = options.on_conflict != OnConflict::Overwrite;
+        if !options.no_git_check && !git::confirm(&target, &overwritten, ask)? {
+            return Err(Box::new(Declined));
+        }
+
+        // Expand the template into the target file
+        let contents = expand_file(&templ, &templ, &mut placeholders, options.raw)?;
+        let mut copy = || -> io::Result<()> {
+            if let Some(parent) = target.parent() {
+                transaction.create_dir_all(parent)?;
+            }
+            transaction.create_file(&target)?;
+            verbose!("Copying {} -> {}", templ.display(), target.display());
+            let mode_of = (!options.no_preserve_mode).then_some(templ.as_path());
+            write_atomic(&target, &contents, mode_of)
+        };
+        if let Err(e) = copy() {
+            transaction.rollback();
+            return Err(Box::new(e));
         }
+        copied.push(Copied::new(&templ, &target, &contents, normalize)?);
+        files.push(target.clone());
+        cursor_line = placeholders.cursor_line();
+    }
 
-        // Copy the template into the target file
-        fs::copy(&templ, &target)?;
+    // Remember the template for bare `take` in this directory
+    let last = LastTemplate {
+        name: path_to_templ(&templ, templ.is_dir())?,
+        path: templ.clone(),
+    };
+    if let Err(e) = history::remember(&env::current_dir()?, last) {
+        eprintln!("Warning: cannot remember the used template: {e}");
     }
 
-    // Open the target file/directory in the default editor
-    let editor = env::var("EDITOR")?;
-    process::Command::new(editor).arg(&target).status()?;
+    // Open the target file/directory in the editor. Unless the user discards
+    // the changes, the backups of the overwritten files are removed afterwards.
+    let mut expand = |file: &Path| expand_file(&templ, file, &mut placeholders, options.raw);
+    let edited = match editor {
+        Some(editor) => edit_target(
+            &target,
+            &editor,
+            cursor_line,
+            &copied,
+            &mut expand,
+            &transaction,
+            options,
+        ),
+        None => Ok(()),
+    };
+    transaction.commit()?;
+    edited?;
+
+    if !options.no_hooks {
+        hooks::run(&metadata.hooks, &target, metadata.hooks_fatal)?;
+    }
+    Ok(Taken {
+        templ: Some(templ),
+        target,
+        files,
+        saved: true,
+    })
+}
 
-    // For normal file templates, check if the target file contents is different
-    // from the template and if not, warn and offer user not to save the target.
-    if templ.is_file() {
-        let mut target_contents = String::new();
-        let mut templ_contents = String::new();
-        fs::File::open(&target)?.read_to_string(&mut target_contents)?;
-        fs::File::open(&templ)?.read_to_string(&mut templ_contents)?;
-        if target_contents == templ_contents {
-            let prompt = "The file contains no change from the template. Save it anyways?";
-            if !user_prompt_bool(&prompt)? {
-                std::fs::remove_file(target)?;
-            }
+/// Open the `target` created from a template in the `editor` and offer to
+/// discard it if none of the `copied` files was changed. The template files
+/// are expanded again by `expand` when printing diffs (see `take` for the
+/// meaning of the other arguments).
+fn edit_target(
+    target: &Path,
+    editor: &str,
+    cursor_line: Option<usize>,
+    copied: &[Copied],
+    expand: &mut ExpandFile,
+    transaction: &Transaction,
+    options: &TakeOptions,
+) -> Result<(), Box<dyn error::Error>> {
+    // If the editor fails, the target may be discarded if it was newly created
+    // (files which existed before are never removed but restored)
+    let discard = || transaction.discard();
+    let discard = transaction
+        .has_created()
+        .then_some(&discard as &dyn Fn() -> io::Result<()>);
+    edit_file(target, editor, cursor_line, discard)?;
+    if options.diff {
+        print_diffs(copied, expand)?;
+    }
+
+    // Check if the created files are different from the (expanded) template
+    // and if not, warn and offer user not to save the target. Trailing
+    // whitespace is ignored as editors often add or remove the final newline.
+    if copied.is_empty() || !unchanged(copied, normalize_compare(options))? {
+        return Ok(());
+    }
+    // Show the ignored (whitespace) changes, if any
+    if !options.diff && !is_quiet() {
+        print_diffs(copied, expand)?;
+    }
+    if !target.is_dir() {
+        let prompt = "The file contains no change from the template. Save it anyways?";
+        if !user_prompt_keep(prompt)? {
+            transaction.discard()?;
+            return Err(Box::new(Declined));
+        }
+    } else if transaction.has_created() {
+        let prompt = "The created files contain no change from the template. Keep them anyways?";
+        if !user_prompt_keep(prompt)? {
+            transaction.discard()?;
+            return Err(Box::new(Declined));
         }
     }
 
     Ok(())
 }
+
+/// Remove the `created` files and directories (in the reverse order of
+/// creation). Directories are removed only if they are empty.
+fn remove_created(created: &[PathBuf]) -> io::Result<()> {
+    for path in created.iter().rev() {
+        match path.is_dir() && !path.is_symlink() {
+            true if path.read_dir()?.next().is_none() => fs::remove_dir(path)?,
+            true => {}
+            false => fs::remove_file(path)?,
+        }
+    }
+    Ok(())
+}
@@ -1,52 +1,1264 @@
 use std::{
-    env, error, fs,
-    io::{self, Write},
-    path::PathBuf,
-    str::FromStr,
+    env, error,
+    ffi::OsStr,
+    fs,
+    hash::{DefaultHasher, Hasher},
+    io::{self, BufRead, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::{
+    config,
+    errors::{
+        Declined, EditorFailed, InvalidTemplateName, NoEditor, NoFileName, NonInteractive,
+        UnreadableDir,
+    },
+};
+
+/// Whether the user may be queried (disabled by `--non-interactive`).
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Whether queries should be answered by their default answers without asking
+/// (enabled by `--yes`).
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Whether file operations should be logged (enabled by `--verbose`).
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Print a message to stderr if `--verbose` was given.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::utils::is_verbose() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use verbose;
+
+/// Print an informational message (e.g. a summary) to stdout unless `--quiet`
+/// was given.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::utils::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use info;
+
+/// Print a notice (e.g. about the selected template) to stderr unless
+/// `--quiet` was given.
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if !$crate::utils::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use notice;
+
+/// Whether template names must match exactly (set by `--exact`).
+static EXACT_NAMES: AtomicBool = AtomicBool::new(false);
+
+/// Directory at which the search for local templates stops (`--search-root`).
+static SEARCH_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the directory at which the search for local templates stops.
+pub fn set_search_root(dir: PathBuf) {
+    let _ = SEARCH_ROOT.set(dir);
+}
+
+/// Files or directories marking the project root at which the search for
+/// local templates stops by default.
+const ROOT_MARKERS: [&str; 2] = [".git", ".templaar"];
+
+/// File or directory marking the project root (`--root`), used instead of
+/// `ROOT_MARKERS`.
+static ROOT_MARKER: OnceLock<String> = OnceLock::new();
+
+/// Set the file or directory marking the project root.
+pub fn set_root_marker(marker: String) {
+    let _ = ROOT_MARKER.set(marker);
+}
+
+/// Directory with local templates used instead of the current directory
+/// (`--template-dir`).
+static TEMPL_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the directory with local templates.
+pub fn set_templ_dir(dir: PathBuf) {
+    let _ = TEMPL_DIR.set(dir);
+}
+
+/// Get the directory in which the search for local templates starts and in
+/// which new local templates are created. It is given by `--template-dir` or
+/// by TEMPLAAR_DIR, defaults to the current directory.
+pub fn local_dir() -> Result<PathBuf, Box<dyn error::Error>> {
+    let cwd = env::current_dir()?;
+    let dir = match TEMPL_DIR.get() {
+        Some(dir) => dir.clone(),
+        None => match env::var_os("TEMPLAAR_DIR") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => return Ok(cwd),
+        },
+    };
+    let dir = cwd.join(dir);
+    if !dir.is_dir() {
+        return Err(Box::new(UnreadableDir {
+            path: dir,
+            reason: "not a directory".to_string(),
+        }));
+    }
+    Ok(dir.canonicalize().unwrap_or(dir))
+}
+
+/// Get the directories searched for local templates: the local templates
+/// directory (see `local_dir`) and its parents up to the search root. The search root is given by
+/// `--search-root` or `search_root` in the config file, by default, the search
+/// stops at the first directory containing a root marker (`.git` or
+/// `.templaar`, or the one given by `--root`) or at the home directory.
+pub fn search_dirs() -> Result<Vec<PathBuf>, Box<dyn error::Error>> {
+    let cwd = env::current_dir()?;
+    // An explicit root marker takes precedence over the config file
+    let config_root = match ROOT_MARKER.get() {
+        Some(_) => None,
+        None => config::get().search_root.as_ref(),
+    };
+    let root = match SEARCH_ROOT.get().or(config_root) {
+        Some(root) => {
+            let root = cwd.join(root);
+            Some(root.canonicalize().unwrap_or(root))
+        }
+        None => None,
+    };
+    let markers = match ROOT_MARKER.get() {
+        Some(marker) => vec![marker.as_str()],
+        None => ROOT_MARKERS.to_vec(),
+    };
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    let mut dirs = vec![];
+    for dir in local_dir()?.ancestors() {
+        dirs.push(dir.to_path_buf());
+        let end = match &root {
+            Some(root) => dir == root,
+            None => markers.iter().any(|m| dir.join(m).exists()) || home.as_deref() == Some(dir),
+        };
+        if end {
+            break;
+        }
+    }
+    Ok(dirs)
+}
+
+/// Whether informational messages should be suppressed (enabled by `--quiet`).
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable suppressing of informational messages.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Check if informational messages should be suppressed.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Whether templates shadowed by the used one should be reported (enabled by
+/// `--show-shadowed`).
+static SHOW_SHADOWED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable reporting of shadowed templates.
+pub fn set_show_shadowed(show: bool) {
+    SHOW_SHADOWED.store(show, Ordering::Relaxed);
+}
+
+/// Check if templates shadowed by the used one should be reported.
+pub fn show_shadowed() -> bool {
+    SHOW_SHADOWED.load(Ordering::Relaxed)
+}
+
+/// Whether template names are matched case-insensitively (enabled by
+/// `--ignore-case`).
+static IGNORE_CASE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable case-insensitive matching of template names.
+pub fn set_ignore_case(ignore: bool) {
+    IGNORE_CASE.store(ignore, Ordering::Relaxed);
+}
+
+/// Check if template names are matched case-insensitively, i.e.
+/// `--ignore-case` was given or `ignore_case` is set in the config file.
+pub fn ignore_case() -> bool {
+    IGNORE_CASE.load(Ordering::Relaxed) || config::get().ignore_case.unwrap_or(false)
+}
+
+/// Enable or disable prefix and glob matching of template names.
+pub fn set_exact_names(exact: bool) {
+    EXACT_NAMES.store(exact, Ordering::Relaxed);
+}
+
+/// Check if template names must match exactly.
+pub fn exact_names() -> bool {
+    EXACT_NAMES.load(Ordering::Relaxed)
+}
+
+/// Enable or disable logging of file operations (see `verbose!`).
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Check if file operations should be logged.
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Enable or disable interactive queries (see `is_interactive`).
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+/// Check if the user can be queried interactively, i.e. interactive queries
+/// were not disabled and stdin is a terminal.
+/// Can be forced by setting TEMPLAAR_INTERACTIVE to 1 (answers are read from
+/// stdin even if it is not a terminal) or 0 (never query the user).
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+        && match env::var("TEMPLAAR_INTERACTIVE").as_deref() {
+            Ok("1") => true,
+            Ok("0") => false,
+            _ => io::stdin().is_terminal(),
+        }
+}
+
+/// Enable or disable answering queries by their default answers.
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+/// Check if queries should be answered by their default answers.
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Get the extension of template files.
+/// Defaults to "aar", can be changed by setting TEMPLAAR_EXT or `extension`
+/// in the config file.
+pub fn templ_ext() -> String {
+    match env::var("TEMPLAAR_EXT") {
+        Ok(ext) if !ext.is_empty() => ext,
+        _ => config::get()
+            .extension
+            .clone()
+            .unwrap_or_else(|| "aar".to_string()),
+    }
+}
+
 /// Encode template name into the corresponding file name.
 ///
-/// The returned filename is:
+/// The returned filename is (for the default extension):
 /// - .`templ`.aar for local templates
 /// - `templ`.aar for global templates
+///
+/// Names of global templates may contain slashes (e.g. `rust/license`) which
+/// place the template into sub-directories. `templ` is the full name, so it
+/// may include the inner extension of the target (e.g. `report.md`).
 pub fn templ_to_path(templ: &str, global: bool) -> PathBuf {
     let prefix = if global { "" } else { "." };
-    PathBuf::from_str(&format!("{prefix}{templ}.aar")).unwrap()
+    PathBuf::from(format!("{prefix}{templ}.{}", templ_ext()))
+}
+
+/// Check that `templ` can be used as a name of a new template, i.e. that
+/// `templ_to_path` places it into the templates directory. Names of local
+/// templates must not contain path separators, names of global templates may
+/// contain slashes separating sub-directories but no empty, `.`, or `..`
+/// components.
+pub fn check_templ_name(templ: &str, global: bool) -> Result<(), InvalidTemplateName> {
+    let invalid = |reason: &str| {
+        Err(InvalidTemplateName {
+            name: templ.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+    let separators = ['/', std::path::MAIN_SEPARATOR];
+    if !global && templ.contains(separators) {
+        return invalid("names of local templates cannot contain path separators");
+    }
+    if templ
+        .split(separators)
+        .any(|c| c.is_empty() || c == "." || c == "..")
+    {
+        return invalid("the name cannot contain empty, '.', or '..' components");
+    }
+    Ok(())
+}
+
+/// Decode the full template name from a file name (inverse to
+/// `templ_to_path`).
+///
+/// The template extension is stripped explicitly (so that other dots in the
+/// name are kept) and so is exactly one leading dot of local templates. The
+/// full name of file templates may contain an inner extension (e.g.
+/// `report.md` for `report.md.aar`) which is kept in the default target.
+pub fn path_to_templ_full(path: &Path) -> Result<String, NoFileName> {
+    let file_name = file_name(path)?;
+    let templ = file_name
+        .strip_suffix(&format!(".{}", templ_ext()))
+        .unwrap_or(&file_name);
+    Ok(templ.strip_prefix('.').unwrap_or(templ).to_string())
+}
+
+/// Decode template name from a file name. Unlike `path_to_templ_full`, the
+/// inner extension of file templates is dropped (see `strip_inner_ext`) while
+/// names of directory templates (`dir`) are kept whole. The decoding is purely
+/// lexical, the template need not exist.
+pub fn path_to_templ(path: &Path, dir: bool) -> Result<String, NoFileName> {
+    let templ = path_to_templ_full(path)?;
+    Ok(match dir {
+        true => templ,
+        false => strip_inner_ext(&templ).to_string(),
+    })
+}
+
+/// Strip the inner extension from a full template name (`report.md` becomes
+/// `report`). Names starting with a dot and without any other dot are kept.
+pub fn strip_inner_ext(templ: &str) -> &str {
+    match templ.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !stem.ends_with('/') && !ext.contains('/') => stem,
+        _ => templ,
+    }
+}
+
+/// Get the file name of `path` as a string.
+pub fn file_name(path: &Path) -> Result<String, NoFileName> {
+    match path.file_name() {
+        Some(name) => Ok(name.to_string_lossy().to_string()),
+        None => Err(NoFileName {
+            path: path.to_path_buf(),
+        }),
+    }
+}
+
+/// Check if the path is a template (has the template extension).
+pub fn is_templ(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new(&templ_ext()))
+}
+
+/// Get all templates in `dir` and its sub-directories (which are not
+/// templates themselves) together with their full names. Templates in
+/// sub-directories are named `SUBDIR/TEMPL`.
+pub fn templs_in_tree(dir: &Path) -> Result<Vec<(String, PathBuf)>, Box<dyn error::Error>> {
+    let mut templates = vec![];
+    for path in fs::read_dir(dir)?.filter_map(|f| f.ok().map(|f| f.path())) {
+        if is_templ(&path) {
+            templates.push((path_to_templ_full(&path)?, path));
+        } else if path.is_dir() && !path.is_symlink() {
+            let dir_name = file_name(&path)?;
+            for (name, templ) in templs_in_tree(&path)? {
+                templates.push((format!("{dir_name}/{name}"), templ));
+            }
+        }
+    }
+    Ok(templates)
+}
+
+/// Check if `contents` are likely binary (contain a null byte or are not
+/// valid UTF-8).
+pub fn is_binary(contents: &[u8]) -> bool {
+    contents.contains(&0) || std::str::from_utf8(contents).is_err()
+}
+
+/// Check that templates can be read from `dir` (given by the user).
+pub fn check_templ_dir(dir: &Path) -> Result<(), UnreadableDir> {
+    match fs::read_dir(dir) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(UnreadableDir {
+            path: dir.to_path_buf(),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// Get all templates in `dir`.
+pub fn templs_in_dir(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn error::Error>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(|f| f.ok().map(|f| f.path()))
+        .filter(|f| is_templ(f))
+        .collect())
+}
+
+/// Names of files with gitignore-style patterns of files which are skipped
+/// when copying a directory template.
+pub const IGNORE_FILES: &[&str] = &[".templaarignore", ".aarignore"];
+
+/// Check if `path` (relative to the template directory) is one of
+/// `IGNORE_FILES` in the template root.
+pub fn is_ignore_file(path: &Path) -> bool {
+    IGNORE_FILES.iter().any(|f| path == Path::new(f))
+}
+
+/// Get patterns of ignored files from `IGNORE_FILES` in `dir` (if they exist).
+fn ignored_in_dir(dir: &Path) -> Result<Gitignore, Box<dyn error::Error>> {
+    let mut builder = GitignoreBuilder::new(dir);
+    for ignore_file in IGNORE_FILES.iter().map(|f| dir.join(f)) {
+        if ignore_file.is_file() {
+            if let Some(e) = builder.add(ignore_file) {
+                return Err(Box::new(e));
+            }
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Name of the placeholder file which marks a directory in a directory
+/// template that should be created even if it contains no other files.
+pub const KEEP_FILE: &str = ".aarkeep";
+
+/// Walk `dir` recursively (skipping files ignored by `IGNORE_FILES` in `dir`)
+/// and return all files and all empty directories. The returned paths are
+/// relative to `dir` and sorted.
+pub fn walk_tree(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Box<dyn error::Error>> {
+    let ignored = ignored_in_dir(dir)?;
+    let mut files = vec![];
+    let mut empty_dirs = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(subdir) = dirs.pop() {
+        let mut empty = true;
+        for entry in fs::read_dir(dir.join(&subdir))? {
+            let entry = entry?;
+            let path = subdir.join(entry.file_name());
+            // Symbolic links (even to directories) are not followed
+            let is_dir = entry.file_type()?.is_dir();
+            if ignored.matched(&path, is_dir).is_ignore() {
+                continue;
+            }
+            empty = false;
+            match is_dir {
+                true => dirs.push(path),
+                false => files.push(path),
+            }
+        }
+        if empty && !subdir.as_os_str().is_empty() {
+            empty_dirs.push(subdir);
+        }
+    }
+    files.sort();
+    empty_dirs.sort();
+    Ok((files, empty_dirs))
+}
+
+/// Get all files in `dir` and its sub-directories (in any depth), except for
+/// files ignored by `IGNORE_FILES` in `dir`. The returned paths are relative to
+/// `dir` and sorted.
+pub fn files_in_tree(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn error::Error>> {
+    Ok(walk_tree(dir)?.0)
+}
+
+/// Find a path (from `paths`) which is a template or is located inside
+/// a template.
+pub fn find_nested_templ(paths: &[PathBuf]) -> Option<&PathBuf> {
+    paths
+        .iter()
+        .find(|p| p.iter().any(|c| is_templ(Path::new(c))))
+}
+
+/// Recursively copy the directory `src` into `dst` (which must not exist).
+/// Files ignored by `IGNORE_FILES` in `src` are skipped, empty directories are
+/// recorded by creating `KEEP_FILE` inside them, and symbolic links are
+/// recreated.
+pub fn copy_dir(src: &Path, dst: &Path) -> Result<(), Box<dyn error::Error>> {
+    fs::create_dir(dst)?;
+    let (files, empty_dirs) = walk_tree(src)?;
+    for file in files {
+        let src_file = src.join(&file);
+        let dst_file = dst.join(&file);
+        verbose!("Copying {} -> {}", src_file.display(), dst_file.display());
+        if let Some(parent) = dst_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match copy_as_symlink(&src_file, false) {
+            true => copy_symlink(&src_file, &dst_file)?,
+            false => {
+                fs::copy(&src_file, &dst_file)?;
+            }
+        }
+    }
+    for dir in empty_dirs {
+        fs::create_dir_all(dst.join(&dir))?;
+        fs::File::create(dst.join(&dir).join(KEEP_FILE))?;
+    }
+    Ok(())
+}
+
+/// Recursively copy the directory `src` into `dst` (which must not exist) as it
+/// is, i.e. without skipping ignored files or adding `KEEP_FILE`s. Symbolic
+/// links are recreated.
+pub fn copy_tree(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            copy_symlink(&src_path, &dst_path)?;
+        } else if file_type.is_dir() {
+            copy_tree(&src_path, &dst_path)?;
+        } else {
+            verbose!("Copying {} -> {}", src_path.display(), dst_path.display());
+            fs::copy(&src_path, &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Get all entries of the tree `path` (without following symbolic links)
+/// together with their sizes. The paths are relative to `path` and sorted.
+fn tree_entries(path: &Path) -> io::Result<Vec<(PathBuf, u64)>> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(vec![(PathBuf::new(), metadata.len())]);
+    }
+    let mut entries = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(path.join(&dir))? {
+            let entry = entry?;
+            let entry_path = dir.join(entry.file_name());
+            let metadata = entry.metadata()?;
+            match metadata.is_dir() {
+                true => {
+                    entries.push((entry_path.clone(), 0));
+                    dirs.push(entry_path);
+                }
+                false => entries.push((entry_path, metadata.len())),
+            }
+        }
+    }
+    entries.sort();
+    Ok(entries)
 }
 
-/// Decode template name from a file name (inverse to `templ_to_path`).
-pub fn path_to_templ(path: &PathBuf) -> String {
-    let mut templ = path.file_stem().unwrap().to_str().unwrap_or("<invalid>");
-    if templ.starts_with(".") {
-        templ = &templ[1..];
+/// Check if the data read from `a` and `b` are the same. Both readers are
+/// read in chunks, so the data is never kept in memory as a whole.
+fn same_contents(mut a: impl Read, mut b: impl Read) -> io::Result<bool> {
+    let mut buf_a = [0; 8192];
+    let mut buf_b = [0; 8192];
+    loop {
+        let n = a.read(&mut buf_a)?;
+        if n == 0 {
+            return Ok(b.read(&mut buf_b)? == 0);
+        }
+        match b.read_exact(&mut buf_b[..n]) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            result => result?,
+        }
+        if buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
     }
-    templ.to_string()
 }
 
-/// Get global templates directory (~/.config/templaar).
+/// Check if `a` and `b` (files or directory trees) contain the same entries
+/// with the same contents, e.g. to verify that a copy is complete. Symbolic
+/// links are compared by their targets.
+pub fn same_tree(a: &Path, b: &Path) -> io::Result<bool> {
+    let entries = tree_entries(a)?;
+    if entries != tree_entries(b)? {
+        return Ok(false);
+    }
+    for (entry, _) in entries {
+        // The only entry of a file is the file itself
+        let (a, b) = match entry.as_os_str().is_empty() {
+            true => (a.to_path_buf(), b.to_path_buf()),
+            false => (a.join(&entry), b.join(&entry)),
+        };
+        let file_type = fs::symlink_metadata(&a)?.file_type();
+        let same = if file_type.is_symlink() {
+            fs::read_link(&a)? == fs::read_link(&b)?
+        } else if file_type.is_dir() {
+            true
+        } else {
+            same_contents(fs::File::open(&a)?, fs::File::open(&b)?)?
+        };
+        if !same {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Check if `path` should be copied as a symbolic link, i.e. it is a link and
+/// links are not dereferenced. Links to directories and broken links (with
+/// a warning) are always copied as links.
+pub fn copy_as_symlink(path: &Path, dereference: bool) -> bool {
+    if !path.is_symlink() {
+        return false;
+    }
+    if !path.exists() {
+        eprintln!(
+            "Warning: {} is a broken symbolic link, copying it as-is",
+            path.display()
+        );
+        return true;
+    }
+    !dereference || path.is_dir()
+}
+
+/// Create a symbolic link at `dst` pointing to the same path as the link
+/// `src` (relative links are kept relative).
+pub fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    let target = fs::read_link(src)?;
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, dst);
+    #[cfg(windows)]
+    return match src.is_dir() {
+        true => std::os::windows::fs::symlink_dir(target, dst),
+        false => std::os::windows::fs::symlink_file(target, dst),
+    };
+}
+
+/// Copy permissions (mode bits) of `src` to `dst`. Does nothing on non-Unix
+/// systems.
+#[cfg(unix)]
+pub fn copy_mode(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::set_permissions(dst, fs::metadata(src)?.permissions())
+}
+
+#[cfg(not(unix))]
+pub fn copy_mode(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Write `contents` to the file at `path` atomically: the contents are written
+/// to a temporary file in the same directory which is then renamed to `path`,
+/// so the file either exists fully or not at all. If `mode_of` is given, its
+/// permissions are copied to the file (see `copy_mode`). The temporary file is
+/// removed on error.
+pub fn write_atomic(path: &Path, contents: &[u8], mode_of: Option<&Path>) -> io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{file_name}.aartmp"));
+    let write = || -> io::Result<()> {
+        fs::write(&tmp, contents)?;
+        if let Some(src) = mode_of {
+            copy_mode(src, &tmp)?;
+        }
+        fs::rename(&tmp, path)
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Check if `a` and `b` are the same file (following symbolic links). Paths
+/// which do not exist are never the same file.
+#[cfg(unix)]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Compute a digest of the text read from `reader`, allowing to check if a
+/// file was changed without keeping its original contents in memory. The text
+/// is read in chunks (or in lines with `normalize`) and trailing whitespace is
+/// ignored. With `normalize`, CRLF line endings are converted to LF and
+/// trailing whitespace is removed from each line, too.
+pub fn text_digest(reader: impl Read, normalize: bool) -> io::Result<u64> {
+    let mut reader = io::BufReader::new(reader);
+    let mut hasher = DefaultHasher::new();
+    let mut chunk = vec![];
+    // Whitespace which is hashed only once it is followed by other characters
+    let mut pending = vec![];
+    loop {
+        chunk.clear();
+        let n = match normalize {
+            true => {
+                let n = reader.read_until(b'\n', &mut chunk)?;
+                let newline = chunk.ends_with(b"\n");
+                chunk.truncate(chunk.trim_ascii_end().len());
+                if newline {
+                    chunk.push(b'\n');
+                }
+                n
+            }
+            false => {
+                let buf = reader.fill_buf()?;
+                chunk.extend_from_slice(buf);
+                let n = buf.len();
+                reader.consume(n);
+                n
+            }
+        };
+        if n == 0 {
+            return Ok(hasher.finish());
+        }
+        match chunk.iter().rposition(|b| !b.is_ascii_whitespace()) {
+            Some(i) => {
+                hasher.write(&pending);
+                hasher.write(&chunk[..=i]);
+                pending.clear();
+                pending.extend_from_slice(&chunk[i + 1..]);
+            }
+            None => pending.extend_from_slice(&chunk),
+        }
+    }
+}
+
+/// Canonicalize `path` which may not exist: its longest existing ancestor is
+/// canonicalized and the remaining components are appended to it.
+pub fn canonicalize_lossy(path: &Path) -> Result<PathBuf, Box<dyn error::Error>> {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            // `ancestor` is always a prefix of `path`
+            return Ok(canonical.join(path.strip_prefix(ancestor)?));
+        }
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Get path to the global templates directory ($XDG_CONFIG_HOME/templaar,
+/// defaults to ~/.config/templaar).
+pub fn global_dir_path() -> Result<PathBuf, Box<dyn error::Error>> {
+    let config_dir = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var("HOME")?).join(".config"),
+    };
+    Ok(config_dir.join("templaar"))
+}
+
+/// Get global templates directory (see `global_dir_path`).
 /// Creates the directory if it doesn't exist.
 pub fn global_dir() -> Result<PathBuf, Box<dyn error::Error>> {
-    let dir: PathBuf = [env::var("HOME")?.as_str(), ".config", "templaar"]
-        .iter()
-        .collect();
+    let dir = global_dir_path()?;
     if !dir.exists() {
-        fs::create_dir(&dir)?;
+        fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Get the editor to use.
+///
+/// The editor is either given explicitly (by `--editor`), taken from the
+/// EDITOR environment variable, or from the config file.
+pub fn get_editor(editor: &Option<String>) -> Result<String, NoEditor> {
+    match editor {
+        Some(e) => Ok(e.clone()),
+        None => env::var("EDITOR")
+            .ok()
+            .or_else(|| config::get().editor.clone())
+            .ok_or(NoEditor),
+    }
+}
+
+/// Whether to wait until the user is done with editors which return
+/// immediately (enabled by `--wait`).
+static WAIT: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable waiting for editors which return immediately.
+pub fn set_wait(wait: bool) {
+    WAIT.store(wait, Ordering::Relaxed);
+}
+
+/// Check if templaar should wait for the editor, i.e. `--wait` was given or
+/// `wait` is set in the config file.
+pub fn wait_for_editor() -> bool {
+    WAIT.load(Ordering::Relaxed) || config::get().wait.unwrap_or(false)
+}
+
+/// Editors known to detach from the terminal (e.g. GUI editors) together with
+/// the argument making them wait until the file is closed.
+const WAIT_ARG_EDITORS: &[(&str, &str)] = &[
+    ("code", "--wait"),
+    ("code-insiders", "--wait"),
+    ("codium", "--wait"),
+    ("subl", "--wait"),
+    ("zed", "--wait"),
+    ("atom", "--wait"),
+    ("mate", "--wait"),
+    ("gedit", "--wait"),
+    ("kate", "--block"),
+    ("gvim", "--nofork"),
+    ("mvim", "--nofork"),
+];
+
+/// Editors known to run in the terminal, i.e. to never return before the user
+/// is done editing.
+const TERMINAL_EDITORS: &[&str] = &[
+    "vi",
+    "vim",
+    "nvim",
+    "nano",
+    "pico",
+    "emacs",
+    "emacsclient",
+    "micro",
+    "kak",
+    "hx",
+    "joe",
+    "mg",
+    "ne",
+    "ed",
+];
+
+/// Editors other than `TERMINAL_EDITORS` which return sooner than this are
+/// considered to have detached (with `--wait`).
+const DETACH_TIME: Duration = Duration::from_secs(2);
+
+/// The longest time to wait for a change of a file edited by a detached editor
+/// when the user cannot be asked.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Get the file name of the editor `program`.
+fn editor_name(program: &str) -> Option<&str> {
+    Path::new(program).file_name()?.to_str()
+}
+
+/// Get the argument making `program` wait until the file is closed, if it is
+/// a known detaching editor.
+fn wait_arg(program: &str) -> Option<&'static str> {
+    let name = editor_name(program)?;
+    WAIT_ARG_EDITORS
+        .iter()
+        .find(|(editor, _)| *editor == name)
+        .map(|(_, arg)| *arg)
+}
+
+/// Wait until the user is done editing `path` with an editor which returned
+/// immediately. The user is asked to confirm it. When the user cannot be
+/// asked, the modification time of a file `path` is polled until it differs
+/// from `modified`, giving up after `WAIT_TIMEOUT`. Directories are not
+/// waited for in that case as editing their files does not change their
+/// modification time.
+fn wait_for_edit(path: &Path, modified: Option<SystemTime>) -> Result<(), Box<dyn error::Error>> {
+    if is_interactive() && !assume_yes() {
+        user_input_line(
+            &format!("Press Enter when you are done editing {}", path.display()),
+            true,
+        )?;
+        return Ok(());
+    }
+    if path.is_dir() {
+        notice!("Not waiting for the editor of directory {}", path.display());
+        return Ok(());
+    }
+    notice!("Waiting for {} to be changed", path.display());
+    let start = Instant::now();
+    while fs::metadata(path).and_then(|m| m.modified()).ok() == modified {
+        if start.elapsed() >= WAIT_TIMEOUT {
+            notice!("Gave up waiting for {} to be changed", path.display());
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    Ok(())
+}
+
+/// Editors known to support opening a file at a line using `+<line>`.
+const LINE_ARG_EDITORS: &[&str] = &[
+    "vi",
+    "vim",
+    "gvim",
+    "nvim",
+    "nano",
+    "emacs",
+    "emacsclient",
+    "micro",
+    "kak",
+    "joe",
+    "mg",
+    "ne",
+];
+
+/// Check if the editor `program` supports the `+<line>` argument.
+/// Can be forced by setting TEMPLAAR_CURSOR_LINE to 1 (supported) or 0 (not
+/// supported).
+fn supports_line_arg(program: &str) -> bool {
+    match env::var("TEMPLAAR_CURSOR_LINE").as_deref() {
+        Ok("1") => true,
+        Ok("0") => false,
+        _ => editor_name(program).is_some_and(|p| LINE_ARG_EDITORS.contains(&p)),
     }
-    return Ok(dir);
+}
+
+/// Open `path` in the `editor`.
+///
+/// The editor command may contain arguments (e.g. "code --wait") which are
+/// split in the same way as by a shell.
+///
+/// If `line` is given and the editor supports it, the file is opened at that
+/// line. Fails if the editor exits with a non-zero status.
+///
+/// With `--wait`, the wait argument is added for known detaching editors (see
+/// `WAIT_ARG_EDITORS`). If any other editor (except for `TERMINAL_EDITORS`)
+/// returns within `DETACH_TIME`, templaar waits until the user is done editing
+/// (see `wait_for_edit`).
+pub fn open_editor(
+    path: &Path,
+    editor: &str,
+    line: Option<usize>,
+) -> Result<(), Box<dyn error::Error>> {
+    let words = shell_words::split(editor)?;
+    let (program, args) = words.split_first().ok_or(NoEditor)?;
+
+    let mut cmd = process::Command::new(program);
+    cmd.args(args);
+    let wait = match wait_for_editor() {
+        true => match wait_arg(program) {
+            Some(arg) if !args.iter().any(|a| a == arg) => {
+                cmd.arg(arg);
+                false
+            }
+            Some(_) => false,
+            None => !editor_name(program).is_some_and(|p| TERMINAL_EDITORS.contains(&p)),
+        },
+        false => false,
+    };
+    if let Some(line) = line.filter(|_| supports_line_arg(program)) {
+        cmd.arg(format!("+{line}"));
+    }
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    let start = Instant::now();
+    let status = cmd.arg(path).status()?;
+    if !status.success() {
+        return Err(Box::new(EditorFailed { status }));
+    }
+    if wait && start.elapsed() < DETACH_TIME {
+        wait_for_edit(path, modified)?;
+    }
+    Ok(())
+}
+
+/// Open `path` in the `editor` (see `open_editor`) and handle a failure of the
+/// editor. The user is asked whether to retry the edit, keep the file as it
+/// is, or remove it using `discard` (if it was freshly created).
+///
+/// When running non-interactively, the file is removed and the error is
+/// returned. If the user chooses to remove the file, `Declined` is returned.
+pub fn edit_file(
+    path: &Path,
+    editor: &str,
+    line: Option<usize>,
+    discard: Option<&dyn Fn() -> io::Result<()>>,
+) -> Result<(), Box<dyn error::Error>> {
+    loop {
+        let e = match open_editor(path, editor, line) {
+            Err(e) if e.is::<EditorFailed>() => e,
+            result => return result,
+        };
+        if assume_yes() || !is_interactive() {
+            if let Some(discard) = discard {
+                discard()?;
+            }
+            return Err(e);
+        }
+        eprintln!("Error: {e}");
+        let mut choices = vec![('r', "retry"), ('k', "keep")];
+        if discard.is_some() {
+            choices.push(('d', "delete"));
+        }
+        match user_prompt_choice(&format!("Editing {} failed", path.display()), &choices)? {
+            'k' => return Ok(()),
+            'd' => {
+                if let Some(discard) = discard {
+                    discard()?;
+                }
+                return Err(Box::new(Declined));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Print `prompt` and read the user's answer (a single line without the line
+/// ending). An empty answer selects the default answer, if `has_default`.
+///
+/// With `--yes`, the default answer is selected without asking. Fails if the
+/// user cannot be queried (see `is_interactive`) or if `--yes` is given and
+/// there is no default answer.
+///
+/// If stdin is closed (end of input), the user is considered to decline and
+/// `Declined` is returned. The default answer is never selected in that case.
+pub fn user_input_line(prompt: &str, has_default: bool) -> Result<String, Box<dyn error::Error>> {
+    if assume_yes() && has_default {
+        info!("{prompt}");
+        return Ok(String::new());
+    }
+    if assume_yes() || !is_interactive() {
+        return Err(Box::new(NonInteractive {
+            prompt: prompt.trim_end().trim_end_matches(':').to_string(),
+        }));
+    }
+
+    let mut buf = String::new();
+    print!("{prompt}");
+    io::stdout().flush()?;
+    if io::stdin().read_line(&mut buf)? == 0 {
+        println!();
+        return Err(Box::new(Declined));
+    }
+
+    Ok(buf.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// Print `prompt` and read the user's answer (a single line, trimmed). An
+/// empty answer selects the default answer (see `user_input_line`).
+pub fn user_input(prompt: &str) -> Result<String, Box<dyn error::Error>> {
+    Ok(user_input_line(prompt, true)?.trim().to_string())
 }
 
 /// Query user for a boolean (yes/no) input.
 ///
 /// Returns true if the user selected "yes".
 ///
-/// Default answer is "yes".
-pub fn user_prompt_bool(prompt: &str) -> Result<bool, Box<dyn error::Error>> {
-    let mut buf = String::new();
-    print!("{} [Y/n]: ", prompt);
+/// Default answer (for an empty or unrecognized input) is given by `default`
+/// and it is rendered in upper case in the hint (`[Y/n]` or `[y/N]`). On the
+/// end of input, `Declined` is returned regardless of the default.
+pub fn user_prompt_bool(prompt: &str, default: bool) -> Result<bool, Box<dyn error::Error>> {
+    let hint = match default {
+        true => "[Y/n]",
+        false => "[y/N]",
+    };
+    Ok(
+        match user_input(&format!("{prompt} {hint}: "))?
+            .to_lowercase()
+            .as_str()
+        {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        },
+    )
+}
 
-    io::stdout().flush()?;
-    io::stdin().read_line(&mut buf)?;
+/// Offer the user to discard a file (e.g. one without changes from the
+/// template) by asking `prompt` whether to keep it.
+///
+/// Returns false if the user chose to discard the file. Unlike other queries,
+/// the file is kept (the default answer) when the user cannot be asked or on
+/// the end of input since discarding it cannot be undone.
+pub fn user_prompt_keep(prompt: &str) -> Result<bool, Box<dyn error::Error>> {
+    if !is_interactive() {
+        return Ok(true);
+    }
+    match user_prompt_bool(prompt, true) {
+        Err(e) if e.is::<Declined>() => Ok(true),
+        result => result,
+    }
+}
+
+/// Query user to select one of the `choices`, each given by a key (which the
+/// user types) and a description.
+///
+/// Returns the key of the selected choice. The query is repeated until a valid
+/// key is given.
+///
+/// Default answer (for an empty input) is the first choice.
+pub fn user_prompt_choice(
+    prompt: &str,
+    choices: &[(char, &str)],
+) -> Result<char, Box<dyn error::Error>> {
+    let options: Vec<String> = choices
+        .iter()
+        .map(|(key, desc)| format!("{key}={desc}"))
+        .collect();
+    let prompt = format!("{} [{}]: ", prompt, options.join(", "));
+    loop {
+        let answer = user_input(&prompt)?;
+        let mut chars = answer.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => return Ok(choices[0].0),
+            (Some(c), None) if choices.iter().any(|(key, _)| *key == c) => return Ok(c),
+            _ => println!("Invalid choice '{answer}'"),
+        }
+    }
+}
+
+/// Query user to select one of the `items`, each given by a name and a
+/// description. The items are printed as a numbered list and the user selects
+/// one by its number or name.
+///
+/// Returns the index of the selected item or None if the user entered an
+/// empty line. On the end of input, `Declined` is returned. The query is
+/// repeated until a valid item is given.
+pub fn user_prompt_select(
+    prompt: &str,
+    items: &[(String, String)],
+) -> Result<Option<usize>, Box<dyn error::Error>> {
+    for (i, (name, desc)) in items.iter().enumerate() {
+        println!("{:>3}) {name} ({desc})", i + 1);
+    }
+    loop {
+        let answer = user_input(&format!("{prompt} (empty to abort): "))?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        let index = match answer.parse::<usize>() {
+            Ok(n) if (1..=items.len()).contains(&n) => Some(n - 1),
+            _ => items.iter().position(|(name, _)| *name == answer),
+        };
+        match index {
+            Some(i) => return Ok(Some(i)),
+            None => println!("Invalid choice '{answer}'"),
+        }
+    }
+}
 
-    Ok(buf.trim().to_lowercase() != "n")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_to_templ() {
+        let full = |path: &str| path_to_templ_full(Path::new(path)).unwrap();
+        assert_eq!(full(".a.b.c.aar"), "a.b.c");
+        assert_eq!(full("a.b.c.aar"), "a.b.c");
+        assert_eq!(full("dir/.gitignore.aar"), "gitignore");
+        assert_eq!(full("..templ.aar"), ".templ");
+        assert_eq!(full(".aar.aar"), "aar");
+        assert!(path_to_templ_full(Path::new("/")).is_err());
+        assert!(path_to_templ_full(Path::new("dir/..")).is_err());
+
+        // The inner extension is dropped for file templates only
+        let file = |path: &str| path_to_templ(Path::new(path), false).unwrap();
+        assert_eq!(file(".a.b.c.aar"), "a.b");
+        assert_eq!(file("a.b.c.aar"), "a.b");
+        assert_eq!(file(".report.md.aar"), "report");
+        assert_eq!(file("dir/.gitignore.aar"), "gitignore");
+        assert_eq!(file("..templ.aar"), ".templ");
+        let dir = |path: &str| path_to_templ(Path::new(path), true).unwrap();
+        assert_eq!(dir(".a.b.c.aar"), "a.b.c");
+        assert_eq!(dir("a.b.c.aar"), "a.b.c");
+        assert_eq!(dir(".proj.d.aar"), "proj.d");
+        assert!(path_to_templ(Path::new("/"), true).is_err());
+    }
+
+    #[test]
+    fn test_check_templ_name() {
+        for name in ["templ", "a.b.c", ".templ", "..templ"] {
+            assert!(check_templ_name(name, false).is_ok());
+            assert!(check_templ_name(name, true).is_ok());
+        }
+        assert!(check_templ_name("rust/license", true).is_ok());
+        assert!(check_templ_name("rust/license", false).is_err());
+        for name in [
+            "",
+            ".",
+            "..",
+            "../x",
+            "../../evil",
+            "a/../b",
+            "/abs",
+            "a//b",
+            "a/",
+        ] {
+            assert!(check_templ_name(name, false).is_err());
+            assert!(check_templ_name(name, true).is_err());
+        }
+    }
+
+    #[test]
+    fn test_strip_inner_ext() {
+        assert_eq!(strip_inner_ext("report.md"), "report");
+        assert_eq!(strip_inner_ext("a.b.c"), "a.b");
+        assert_eq!(strip_inner_ext("report"), "report");
+        assert_eq!(strip_inner_ext(".templ"), ".templ");
+        assert_eq!(strip_inner_ext("rust/license.txt"), "rust/license");
+        assert_eq!(strip_inner_ext("rust.d/license"), "rust.d/license");
+        assert_eq!(strip_inner_ext("rust/.license"), "rust/.license");
+    }
+
+    #[test]
+    fn test_text_digest() {
+        let same = |a: &str, b: &str, normalize| {
+            text_digest(a.as_bytes(), normalize).unwrap()
+                == text_digest(b.as_bytes(), normalize).unwrap()
+        };
+        let large = "x".repeat(200 * 1024);
+        for (a, b, equal) in [
+            ("templ", "templ", true),
+            ("templ\n\n", "templ", true),
+            ("templ", "templ \n", true),
+            ("temp", "templ", false),
+            ("templ!", "templ", false),
+            ("", "", true),
+            ("", " \n", true),
+            (&format!("{large}\n"), &large, true),
+            (&format!("{large}y"), &large, false),
+            (
+                &format!("{large}    {large}"),
+                &format!("{large}{large}"),
+                false,
+            ),
+            // Line endings and whitespace on lines are kept
+            ("a\r\nb", "a\nb", false),
+            ("a \nb", "a\nb", false),
+        ] {
+            assert_eq!(same(a, b, false), equal);
+        }
+
+        // CRLF template edited on Unix
+        assert!(same("a\r\nb  \r\n", "a\nb\n", true));
+        // LF template edited on Windows
+        assert!(same("a\nb", "a\r\nb\r\n", true));
+        // Trailing whitespace on lines and a missing final newline
+        assert!(same("a \t\nb\n\n", "a\nb", true));
+        // Leading and inner whitespace is kept
+        assert!(!same(" a\nb", "a\nb", true));
+        assert!(!same("a\n\nb", "a\nb", true));
+        assert!(!same("a b", "ab", true));
+    }
+
+    #[test]
+    fn test_same_contents() {
+        let same = |a: &[u8], b: &[u8]| same_contents(a, b).unwrap();
+        let large = vec![b'x'; 20000];
+        let mut changed = large.clone();
+        changed[10000] = b'y';
+        assert!(same(b"templ", b"templ"));
+        assert!(same(b"", b""));
+        assert!(same(&large, &large));
+        assert!(!same(b"templ", b"temp"));
+        assert!(!same(b"temp", b"templ"));
+        assert!(!same(b"templ", b"tempL"));
+        assert!(!same(&large, &changed));
+        assert!(!same(&large, &large[..19999]));
+    }
+
+    #[test]
+    fn test_templ_to_path() {
+        for name in ["a.b.c", "gitignore", "templ"] {
+            assert_eq!(
+                path_to_templ_full(&templ_to_path(name, false)).unwrap(),
+                name
+            );
+            assert_eq!(
+                path_to_templ_full(&templ_to_path(name, true)).unwrap(),
+                name
+            );
+            assert_eq!(
+                path_to_templ(&templ_to_path(name, false), true).unwrap(),
+                name
+            );
+        }
+    }
 }
@@ -0,0 +1,17 @@
+use std::error;
+
+use crate::take::{find_templ, no_templ_found};
+
+/// The handler of the `which` sub-command.
+///
+/// Prints the path to the template which would be used by `take` with the
+/// same arguments.
+///
+/// # Arguments
+///
+/// * `template` - Optional name of the template to use
+pub fn which(template: &Option<String>) -> Result<(), Box<dyn error::Error>> {
+    let templ = find_templ(template, &None)?.ok_or_else(|| no_templ_found(&None))?;
+    println!("{}", templ.display());
+    Ok(())
+}
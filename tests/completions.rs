@@ -0,0 +1,36 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{collections::HashMap, error::Error};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_completions() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("completions", vec![], HashMap::new(), "touch");
+
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("completions").arg(shell);
+        let output = cmd.output()?;
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout)?;
+        assert!(stdout.contains("templaar"));
+        assert!(stdout.contains("list-vars"));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_completions_invalid_shell() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("completions_invalid_shell", vec![], HashMap::new(), "touch");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("completions").arg("tcsh");
+    cmd.assert().failure();
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{collections::HashMap, env, error::Error, fs, path::Path};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_copy() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "copy",
+        vec![Path::new(".dir.aar").join("src"), config_dir.clone()],
+        HashMap::from([
+            (
+                Path::new(".templ.aar").to_path_buf(),
+                "Template".to_string(),
+            ),
+            (
+                Path::new(".dir.aar").join("src").join("main.rs"),
+                "fn main() {}".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    // File template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("copy").arg("templ").arg("variant");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(".variant.aar")?, "Template");
+    assert_eq!(fs::read_to_string(".templ.aar")?, "Template");
+
+    // Directory template copied into the global directory
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("copy").arg("dir").arg("rust/bin").arg("--global");
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(
+            config_dir
+                .join("rust")
+                .join("bin.aar")
+                .join("src")
+                .join("main.rs")
+        )?,
+        "fn main() {}"
+    );
+
+    // The destination already exists
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("copy").arg("dir").arg("variant");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("already exists"));
+    assert!(Path::new(".variant.aar").is_file());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_copy_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = Path::new(".web.aar");
+    let _t = Test::init(
+        "copy_dir",
+        vec![templ_dir.join("assets")],
+        HashMap::from([
+            (templ_dir.join(".aarignore"), "*.log\n".to_string()),
+            (templ_dir.join("debug.log"), "Log".to_string()),
+            (templ_dir.join("index.html"), "Index".to_string()),
+            (templ_dir.join("assets").join(".aarkeep"), String::new()),
+        ]),
+        "touch",
+    );
+
+    // The directory template is copied as it is
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("copy").arg("web").arg("site");
+    cmd.assert().success();
+    let copy_dir = Path::new(".site.aar");
+    assert_eq!(fs::read_to_string(copy_dir.join(".aarignore"))?, "*.log\n");
+    assert_eq!(fs::read_to_string(copy_dir.join("debug.log"))?, "Log");
+    assert_eq!(fs::read_to_string(copy_dir.join("index.html"))?, "Index");
+    assert!(copy_dir.join("assets").join(".aarkeep").is_file());
+
+    // The copy is removed if the editor fails
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("EDITOR", "false")
+        .env("TEMPLAAR_INTERACTIVE", "0")
+        .arg("copy")
+        .arg("web")
+        .arg("failed");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("The editor failed"));
+    assert!(!Path::new(".failed.aar").exists());
+    assert!(templ_dir.join("index.html").is_file());
+
+    Ok(())
+}
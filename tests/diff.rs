@@ -0,0 +1,129 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_diff() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "diff",
+        vec![],
+        HashMap::from([
+            (
+                PathBuf::from_str(".templ.aar")?,
+                "---aar\ntarget = \"notes.md\"\n---\nfirst\nsecond\n".to_string(),
+            ),
+            (
+                PathBuf::from_str("notes.md")?,
+                "first\nchanged\n".to_string(),
+            ),
+            (PathBuf::from_str("same.md")?, "first\nsecond\n".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("diff");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("notes.md"));
+    assert!(stdout.contains("@@ -1,2 +1,2 @@\n first\n-second\n+changed\n"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("diff").arg("same.md");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("diff").arg("missing.md");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_diff_placeholders() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".proj.aar")?;
+    let _t = Test::init(
+        "diff_placeholders",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (
+                PathBuf::from_str(".note.aar")?,
+                "# {{name}}\n{{cursor}}{{title:Notes}}\n{{missing}}\n".to_string(),
+            ),
+            (templ_dir.join("{{name}}.txt"), "{{name}}\n".to_string()),
+        ]),
+        "touch",
+    );
+
+    // An untouched target has no differences
+    for args in [["n.md", "note"], ["app", "proj"]] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take")
+            .arg(args[0])
+            .arg("-t")
+            .arg(args[1])
+            .arg("--no-edit");
+        cmd.assert().success();
+
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("diff").arg(args[0]).arg("-t").arg(args[1]);
+        let output = cmd.output()?;
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+    assert!(Path::new("app/app.txt").is_file());
+
+    fs::write("n.md", "# n.md\nNotes\nfilled\n")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("diff").arg("n.md").arg("-t").arg("note");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("-{{missing}}\n+filled\n"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_diff_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let target_dir = Path::new("project");
+    let _t = Test::init(
+        "diff_dir",
+        vec![templ_dir.join("src"), target_dir.join("src")],
+        HashMap::from([
+            (templ_dir.join("README"), "readme\n".to_string()),
+            (templ_dir.join("src").join("main.rs"), "main\n".to_string()),
+            (target_dir.join("README"), "readme\n".to_string()),
+            (
+                target_dir.join("src").join("main.rs"),
+                "changed\n".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("diff").arg("project");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(!stdout.contains("README"));
+    assert!(stdout.contains("-main\n+changed\n"));
+
+    Ok(())
+}
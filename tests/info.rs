@@ -0,0 +1,73 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_info() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let templ_dir = PathBuf::from_str(".web.aar")?;
+    let _t = Test::init(
+        "info",
+        vec![config_dir.clone(), templ_dir.join("css")],
+        HashMap::from([
+            (
+                PathBuf::from_str(".notes.md.aar")?,
+                "---aar\ndescription = \"Meeting notes\"\n---\nNotes".to_string(),
+            ),
+            (templ_dir.join("index.html"), "Index".to_string()),
+            (templ_dir.join("css/style.css"), "Style".to_string()),
+            (config_dir.join("license.aar"), "MIT".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+    let cwd = env::current_dir()?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("info").arg("notes");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with(&format!(
+        "Name:        notes\n\
+         Path:        {}\n\
+         Scope:       local\n\
+         Type:        file\n\
+         Size:        46 B\n\
+         Modified:    ",
+        cwd.join(".notes.md.aar").display()
+    )));
+    assert!(stdout.ends_with("\nDescription: Meeting notes\n"));
+
+    // Directory templates list their files
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("info").arg("web");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Type:        directory\nSize:        10 B\n"));
+    assert!(stdout.ends_with("\nFiles:\n  css/style.css\n  index.html\n"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("info").arg("license");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Scope:       global\n"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("info").arg("missing");
+    cmd.assert().failure();
+
+    Ok(())
+}
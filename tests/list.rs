@@ -0,0 +1,362 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_list() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (config_dir.join("other.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, "templ  [local]\nother  [global]\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_paths() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_paths",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (config_dir.join("other.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--paths");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let cwd = env::current_dir()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout,
+        format!(
+            "templ  [local]   {}\nother  [global]  {}\n",
+            cwd.join(".templ.aar").display(),
+            cwd.join(&config_dir).join("other.aar").display()
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_parent() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let subdir = Path::new("testdir");
+    let _t = Test::init(
+        "list_parent",
+        vec![config_dir.to_path_buf(), subdir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (subdir.join(".note.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    env::set_current_dir(subdir)?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, "note   [local]\ntempl  [local ..]\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_sorted() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_sorted",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".beta.aar")?, String::new()),
+            (PathBuf::from_str(".gamma.aar")?, String::new()),
+            (PathBuf::from_str(".alpha.aar")?, String::new()),
+            (config_dir.join("zeta.aar"), String::new()),
+            (config_dir.join("delta.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout,
+        "alpha  [local]\n\
+         beta   [local]\n\
+         gamma  [local]\n\
+         delta  [global]\n\
+         zeta   [global]\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_description() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_description",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (
+                PathBuf::from_str(".templ.aar")?,
+                "---aar\ndescription = \"Meeting notes\"\n---\n".to_string(),
+            ),
+            (config_dir.join("other.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, "templ  [local]   Meeting notes\nother  [global]\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_names_only() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_names_only",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (PathBuf::from_str(".note.aar")?, String::new()),
+            (config_dir.join("templ.aar"), String::new()),
+            (config_dir.join("other.aar"), String::new()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--names-only");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "note\nother\ntempl\n");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--names-only").arg("--local");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "note\ntempl\n");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--names-only").arg("--global");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "other\ntempl\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_from_dir() -> Result<(), Box<dyn Error>> {
+    let shared_dir = Path::new("shared");
+    let _t = Test::init(
+        "list_from_dir",
+        vec![shared_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (shared_dir.join("note.aar"), String::new()),
+            (shared_dir.join("web.aar"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--from-dir").arg("shared");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, "note  [shared]\nweb   [shared]\n");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--from-dir").arg("missing");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Cannot read directory missing"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_global_nested() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_global_nested",
+        vec![config_dir.join("rust").join("ci")],
+        HashMap::from([
+            (config_dir.join("other.aar"), String::new()),
+            (config_dir.join("rust").join("license.aar"), String::new()),
+            (
+                config_dir.join("rust").join("ci").join("gh.aar"),
+                String::new(),
+            ),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--global").arg("--names-only");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout, "other\nrust/ci/gh\nrust/license\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_tree() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "list_tree",
+        vec![
+            config_dir.join("rust").join("ci"),
+            PathBuf::from_str("sub")?,
+        ],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (PathBuf::from_str("sub/.note.aar")?, String::new()),
+            (config_dir.join("other.aar"), String::new()),
+            (config_dir.join("rust").join("license.aar"), String::new()),
+            (
+                config_dir.join("rust").join("ci").join("gh.aar"),
+                String::new(),
+            ),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("sub").arg("list").arg("--tree");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout,
+        "local\n  note\nlocal ..\n  templ\nglobal\n  other\n  rust/\n    ci/\n      gh\n    license\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_list_long() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let templ_dir = PathBuf::from_str(".dir.aar")?;
+    let _t = Test::init(
+        "list_long",
+        vec![config_dir.to_path_buf(), templ_dir.join("src")],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "12345".to_string()),
+            (templ_dir.join("README"), "123".to_string()),
+            (templ_dir.join("src").join("main.rs"), "1234".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+    env::set_var("TZ", "UTC");
+
+    // 2024-01-02 03:04 UTC
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_164_640);
+    for path in [".templ.aar", ".dir.aar/README", ".dir.aar/src/main.rs"] {
+        fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(time)?;
+    }
+    // Directory templates show the latest time of their files
+    fs::File::options()
+        .write(true)
+        .open(".dir.aar/README")?
+        .set_modified(time + Duration::from_secs(24 * 3600))?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--long");
+    let output = cmd.output()?;
+    env::remove_var("TZ");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout,
+        "dir    [local]  7  2024-01-03 03:04\n\
+         templ  [local]  5  2024-01-02 03:04\n"
+    );
+
+    Ok(())
+}
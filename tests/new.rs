@@ -19,7 +19,7 @@ fn test_new_default() -> Result<(), Box<dyn Error>> {
     let _t = Test::init("new_default", vec![], HashMap::new(), "touch");
 
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("new");
+    cmd.arg("new").write_stdin("\n");
     cmd.assert().success();
 
     assert!(Path::new(".templ.aar").exists());
@@ -54,6 +54,12 @@ fn test_new_name_from_stdin() -> Result<(), Box<dyn Error>> {
     let templ_path = Path::new(".stdin_name.aar");
     assert!(templ_path.exists());
 
+    // No template is created if stdin is closed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").write_stdin("");
+    cmd.assert().failure().code(3);
+    assert!(!Path::new(".templ.aar").exists());
+
     Ok(())
 }
 
@@ -71,7 +77,7 @@ fn test_new_global() -> Result<(), Box<dyn Error>> {
     env::set_var("HOME", env::current_dir()?.join(home_dir));
 
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("new").arg("--global");
+    cmd.arg("new").arg("--global").write_stdin("\n\n");
     cmd.assert().success();
 
     let templ_path = config_dir.join("templ.aar");
@@ -83,6 +89,82 @@ fn test_new_global() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+#[serial]
+fn test_new_global_confirm() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "new_global_confirm",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([(PathBuf::from_str("file")?, "File".to_string())]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("--global")
+        .arg("templ")
+        .write_stdin("n\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("Create global template"));
+    assert!(!config_dir.join("templ.aar").exists());
+
+    // The confirmation is skipped with --yes and without a terminal
+    for args in [vec!["--yes"], vec![]] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("new")
+            .arg("--global")
+            .arg("templ")
+            .arg("-f")
+            .arg("file")
+            .arg("--no-edit")
+            .args(args)
+            .env_remove("TEMPLAAR_INTERACTIVE");
+        cmd.assert().success();
+        assert!(config_dir.join("templ.aar").exists());
+        fs::remove_file(config_dir.join("templ.aar"))?;
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_global_nested() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "new_global_nested",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([(PathBuf::from_str("LICENSE")?, "MIT".to_string())]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("--global")
+        .arg("rust/license")
+        .arg("-f")
+        .arg("LICENSE")
+        .write_stdin("y\n");
+    cmd.assert().success();
+    assert!(config_dir.join("rust").join("license.aar").is_file());
+
+    // The template can be taken using its nested name
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("rust/license");
+    cmd.assert().success();
+    let mut contents = String::new();
+    fs::File::open("license")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "MIT");
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn test_new_exists() -> Result<(), Box<dyn Error>> {
@@ -118,12 +200,42 @@ fn test_new_from_file() -> Result<(), Box<dyn Error>> {
     let templ_path = Path::new(".templ.aar");
     let mut contents = String::new();
     assert!(templ_path.exists());
-    fs::File::open(&templ_path)?.read_to_string(&mut contents)?;
+    fs::File::open(templ_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, templ_content);
 
     Ok(())
 }
 
+#[test]
+#[serial]
+fn test_new_from_file_dir() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "new_from_file_dir",
+        vec![PathBuf::from_str("src")?],
+        HashMap::from([(PathBuf::from_str("src/main.rs")?, "Template".to_string())]),
+        "touch",
+    );
+
+    // A single file is stored under its (relative) path in a directory
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("--dir")
+        .arg("-f")
+        .arg("src/main.rs");
+    cmd.assert().success();
+    assert!(Path::new(".templ.aar").is_dir());
+    assert_eq!(fs::read_to_string(".templ.aar/src/main.rs")?, "Template");
+
+    // Without files, an empty directory template is created
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("empty").arg("--dir");
+    cmd.assert().success();
+    assert!(Path::new(".empty.aar").is_dir());
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn test_new_from_multiple_files() -> Result<(), Box<dyn Error>> {
@@ -155,16 +267,409 @@ fn test_new_from_multiple_files() -> Result<(), Box<dyn Error>> {
     assert!(file2_path.exists());
 
     let mut contents = String::new();
-    fs::File::open(&file1_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file1_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, file1_content);
 
     contents.clear();
-    fs::File::open(&file2_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file2_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, file2_content);
 
     Ok(())
 }
 
+#[test]
+#[serial]
+fn test_new_from_nested_files() -> Result<(), Box<dyn Error>> {
+    let src_dir = PathBuf::from_str("src")?;
+    let nested_dir = src_dir.join("nested");
+    let _t = Test::init(
+        "new_from_nested_files",
+        vec![nested_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str("Cargo.toml")?, "[package]".to_string()),
+            (src_dir.join("main.rs"), "fn main() {}".to_string()),
+            (nested_dir.join("mod.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("Cargo.toml")
+        .arg("src");
+    cmd.assert().success();
+
+    let templ_path = Path::new(".templ.aar");
+    assert!(templ_path.join("Cargo.toml").is_file());
+    assert!(templ_path
+        .join("src")
+        .join("nested")
+        .join("mod.rs")
+        .is_file());
+
+    let mut contents = String::new();
+    fs::File::open(templ_path.join("src").join("main.rs"))?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "fn main() {}");
+
+    // Nested files given by a path are stored under the path
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("other")
+        .arg("-f")
+        .arg("Cargo.toml")
+        .arg(src_dir.join("main.rs"));
+    cmd.assert().success();
+
+    assert!(Path::new(".other.aar")
+        .join("src")
+        .join("main.rs")
+        .is_file());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_from_dir_ignore() -> Result<(), Box<dyn Error>> {
+    let project_dir = PathBuf::from_str("project")?;
+    let _t = Test::init(
+        "new_from_dir_ignore",
+        vec![project_dir.join("target")],
+        HashMap::from([
+            (
+                project_dir.join(".templaarignore"),
+                ".DS_Store\ntarget/\n".to_string(),
+            ),
+            (project_dir.join(".DS_Store"), String::new()),
+            (project_dir.join("target").join("out"), String::new()),
+            (project_dir.join("main.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("templ").arg("-f").arg("project");
+    cmd.assert().success();
+
+    let templ_path = Path::new(".templ.aar");
+    assert!(templ_path.join("main.rs").is_file());
+    assert!(templ_path.join(".templaarignore").is_file());
+    assert!(!templ_path.join(".DS_Store").exists());
+    assert!(!templ_path.join("target").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_from_dir_aarignore() -> Result<(), Box<dyn Error>> {
+    let project_dir = PathBuf::from_str("project")?;
+    let _t = Test::init(
+        "new_from_dir_aarignore",
+        vec![project_dir.join("target")],
+        HashMap::from([
+            (
+                project_dir.join(".aarignore"),
+                "*.swp\n!keep.swp\ntarget/\n".to_string(),
+            ),
+            (project_dir.join("target").join("out"), String::new()),
+            (project_dir.join("main.rs.swp"), String::new()),
+            (project_dir.join("keep.swp"), String::new()),
+            (project_dir.join("main.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("templ").arg("-f").arg("project");
+    cmd.assert().success();
+
+    let templ_path = Path::new(".templ.aar");
+    assert!(templ_path.join("main.rs").is_file());
+    assert!(templ_path.join("keep.swp").is_file());
+    assert!(!templ_path.join("main.rs.swp").exists());
+    assert!(!templ_path.join("target").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_from_dir_empty_dirs() -> Result<(), Box<dyn Error>> {
+    let project_dir = PathBuf::from_str("project")?;
+    let _t = Test::init(
+        "new_from_dir_empty_dirs",
+        vec![project_dir.join("logs")],
+        HashMap::from([(project_dir.join("main.rs"), String::new())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("templ").arg("-f").arg("project");
+    cmd.assert().success();
+
+    let templ_path = Path::new(".templ.aar");
+    assert!(templ_path.join("main.rs").is_file());
+    assert!(templ_path.join("logs").join(".aarkeep").is_file());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_new_from_dir_symlinks() -> Result<(), Box<dyn Error>> {
+    let project_dir = PathBuf::from_str("project")?;
+    let _t = Test::init(
+        "new_from_dir_symlinks",
+        vec![project_dir.clone()],
+        HashMap::from([(project_dir.join("v2.conf"), String::new())]),
+        "touch",
+    );
+    std::os::unix::fs::symlink("v2.conf", project_dir.join("latest.conf"))?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("templ").arg("-f").arg("project");
+    cmd.assert().success();
+
+    let templ_path = Path::new(".templ.aar");
+    assert!(templ_path.join("v2.conf").is_file());
+    assert_eq!(
+        fs::read_link(templ_path.join("latest.conf"))?,
+        Path::new("v2.conf")
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_new_from_file_symlink() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "new_from_file_symlink",
+        vec![],
+        HashMap::from([(PathBuf::from_str("v2.conf")?, String::new())]),
+        "touch",
+    );
+    std::os::unix::fs::symlink("v2.conf", "latest.conf")?;
+
+    // A single file is kept as a link, too
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("single").arg("-f").arg("latest.conf");
+    cmd.assert().success();
+    assert_eq!(fs::read_link(".single.aar")?, Path::new("v2.conf"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("multi")
+        .arg("-f")
+        .arg("latest.conf")
+        .arg("v2.conf");
+    cmd.assert().success();
+    let templ_path = Path::new(".multi.aar");
+    assert!(templ_path.join("v2.conf").is_file());
+    assert_eq!(
+        fs::read_link(templ_path.join("latest.conf"))?,
+        Path::new("v2.conf")
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_new_from_file_preserve_mode() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _t = Test::init(
+        "new_from_file_preserve_mode",
+        vec![],
+        HashMap::from([(PathBuf::from_str("run.sh")?, "#!/bin/sh".to_string())]),
+        "touch",
+    );
+    fs::set_permissions("run.sh", fs::Permissions::from_mode(0o755))?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("script").arg("-f").arg("run.sh");
+    cmd.assert().success();
+
+    let mode = fs::metadata(".script.aar")?.permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_from_stdin() -> Result<(), Box<dyn Error>> {
+    let templ_content = "Template\nfrom stdin\n";
+    let _t = Test::init("new_from_stdin", vec![], HashMap::new(), "false");
+    env::remove_var("EDITOR");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("--stdin")
+        .write_stdin(templ_content);
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open(".templ.aar")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    // The template name is required
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("--stdin").write_stdin(templ_content);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_no_edit() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "new_no_edit",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str("foo")?, "foo".to_string()),
+            (PathBuf::from_str("bar")?, "bar".to_string()),
+        ]),
+        "false",
+    );
+    env::remove_var("EDITOR");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("foo")
+        .arg("bar")
+        .arg("--no-edit");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open(".templ.aar/foo")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "foo");
+    assert!(Path::new(".templ.aar/bar").is_file());
+
+    // Without files, an empty template is created
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("empty").arg("--no-edit");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(".empty.aar")?, "");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_editor_failed() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "new_editor_failed",
+        vec![],
+        HashMap::from([(PathBuf::from_str("file")?, "contents".to_string())]),
+        "false",
+    );
+
+    // The new template is deleted on request
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("file")
+        .write_stdin("d\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("r=retry, k=keep, d=delete"));
+    assert!(!Path::new(".templ.aar").exists());
+
+    // Without a terminal, the new template is removed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("file")
+        .env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().failure().code(1);
+    assert!(!Path::new(".templ.aar").exists());
+
+    // The template may be kept
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("file")
+        .write_stdin("k\n");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(".templ.aar")?, "contents");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_template_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = Path::new("templates");
+    let _t = Test::init(
+        "new_template_dir",
+        vec![templ_dir.to_path_buf()],
+        HashMap::from([(PathBuf::from_str("file")?, "contents".to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("templ")
+        .arg("-f")
+        .arg("file")
+        .arg("--template-dir")
+        .arg("templates");
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(templ_dir.join(".templ.aar"))?,
+        "contents"
+    );
+    assert!(!Path::new(".templ.aar").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_empty() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("new_empty", vec![], HashMap::new(), "touch");
+
+    // Empty template is deleted on request
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("empty").write_stdin("n\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("The template is empty"));
+    assert!(!Path::new(".empty.aar").exists());
+
+    // Empty template is kept by default
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("kept").write_stdin("\n");
+    cmd.assert().success();
+    assert!(Path::new(".kept.aar").exists());
+
+    // No question for a template with contents
+    set_editor("sh -c 'echo contents > \"$0\"'");
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("full");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout)?.contains("The template is empty"));
+    assert_eq!(fs::read_to_string(".full.aar")?, "contents\n");
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn test_new_from_file_exists() -> Result<(), Box<dyn Error>> {
@@ -184,7 +689,9 @@ fn test_no_editor() -> Result<(), Box<dyn Error>> {
 
     let mut cmd = Command::cargo_bin("templaar")?;
     cmd.arg("new").arg("note");
-    cmd.assert().failure();
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Set $EDITOR or use the --editor option"));
 
     Ok(())
 }
@@ -200,3 +707,150 @@ fn test_invalid_editor() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+#[serial]
+fn test_editor_option() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("editor_option", vec![], HashMap::new(), "invalid");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("note").arg("--editor").arg("touch");
+    cmd.assert().success();
+
+    assert!(Path::new(".note.aar").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_editor_with_args() -> Result<(), Box<dyn Error>> {
+    let templ_content = "Template";
+    let _t = Test::init(
+        "editor_with_args",
+        vec![],
+        HashMap::from([(PathBuf::from_str("my file")?, templ_content.to_string())]),
+        "cp 'my file'",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("note");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open(".note.aar")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_custom_ext() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("new_custom_ext", vec![], HashMap::new(), "touch");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").arg("note").env("TEMPLAAR_EXT", "tmpl");
+    cmd.assert().success();
+
+    assert!(Path::new(".note.tmpl").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_config() -> Result<(), Box<dyn Error>> {
+    let config_dir = Path::new("config").join("templaar");
+    let _t = Test::init(
+        "new_config",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([(
+            config_dir.join("config.toml"),
+            "editor = \"touch\"\nextension = \"tmpl\"\ndefault_template_name = \"note\"\n"
+                .to_string(),
+        )]),
+        "",
+    );
+    env::remove_var("EDITOR");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .env("XDG_CONFIG_HOME", env::current_dir()?.join("config"))
+        .write_stdin("\n");
+    cmd.assert().success();
+
+    assert!(Path::new(".note.tmpl").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_config_malformed() -> Result<(), Box<dyn Error>> {
+    let config_dir = Path::new("config").join("templaar");
+    let _t = Test::init(
+        "config_malformed",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([(
+            config_dir.join("config.toml"),
+            "editor = \"touch\"\neditr = \"vim\"\n".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("note")
+        .env("XDG_CONFIG_HOME", env::current_dir()?.join("config"));
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.starts_with("Error: Invalid config file"));
+    assert!(stderr.contains("line 2"));
+    assert!(!Path::new(".note.aar").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_invalid_name() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("new_invalid_name", vec![], HashMap::new(), "touch");
+
+    for name in ["../x", "dir/x", ".."] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("new").arg(name);
+        let output = cmd.output()?;
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr)?.contains("Invalid template name"));
+    }
+    assert!(!Path::new("../.x.aar").exists());
+    assert!(!Path::new(".dir").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_new_non_interactive() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("new_non_interactive", vec![], HashMap::new(), "touch");
+
+    // Without a terminal, the user is not asked for the name
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new").env_remove("TEMPLAAR_INTERACTIVE");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Enter template name"));
+    assert!(!Path::new(".templ.aar").exists());
+
+    // With --yes, the default name is used and the empty template is kept
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("new")
+        .arg("--yes")
+        .env_remove("TEMPLAAR_INTERACTIVE");
+    cmd.assert().success();
+    assert!(Path::new(".templ.aar").exists());
+
+    Ok(())
+}
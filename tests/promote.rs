@@ -0,0 +1,115 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{collections::HashMap, env, error::Error, fs, path::Path};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_promote() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "promote",
+        vec![Path::new(".dir.aar").to_path_buf()],
+        HashMap::from([
+            (
+                Path::new(".templ.aar").to_path_buf(),
+                "Template".to_string(),
+            ),
+            (Path::new(".dir.aar").join("file"), "File".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("templ");
+    cmd.assert().success();
+    assert!(!Path::new(".templ.aar").exists());
+    assert_eq!(
+        fs::read_to_string(config_dir.join("templ.aar"))?,
+        "Template"
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("dir");
+    cmd.assert().success();
+    assert!(!Path::new(".dir.aar").exists());
+    assert_eq!(
+        fs::read_to_string(config_dir.join("dir.aar").join("file"))?,
+        "File"
+    );
+
+    // Missing local template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("templ");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("does not exist"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_demote() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "demote",
+        vec![config_dir.clone(), config_dir.join("dir.aar/empty")],
+        HashMap::from([
+            (config_dir.join("templ.aar"), "Template".to_string()),
+            (config_dir.join("dir.aar/.aarignore"), "*.log\n".to_string()),
+            (config_dir.join("dir.aar/debug.log"), "Log".to_string()),
+            (config_dir.join("dir.aar/file"), "File".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("templ").arg("--demote");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(".templ.aar")?, "Template");
+    assert!(config_dir.join("templ.aar").exists());
+
+    // Directory templates are copied as they are, including ignored files
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("dir").arg("--demote");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(".dir.aar/.aarignore")?, "*.log\n");
+    assert_eq!(fs::read_to_string(".dir.aar/debug.log")?, "Log");
+    assert!(Path::new(".dir.aar/empty").is_dir());
+    assert!(!Path::new(".dir.aar/empty/.aarkeep").exists());
+
+    // Promoting it back keeps the ignored files, too
+    fs::remove_dir_all(config_dir.join("dir.aar"))?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("dir");
+    cmd.assert().success();
+    assert!(!Path::new(".dir.aar").exists());
+    assert_eq!(
+        fs::read_to_string(config_dir.join("dir.aar/debug.log"))?,
+        "Log"
+    );
+
+    // The local template already exists
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("templ").arg("--demote");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("already exists"));
+
+    // The global template already exists
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("promote").arg("templ");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("already exists"));
+    assert!(Path::new(".templ.aar").exists());
+
+    Ok(())
+}
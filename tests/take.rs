@@ -31,7 +31,7 @@ fn test_take_same_dir() -> Result<(), Box<dyn Error>> {
     let file_path = Path::new("templ");
     let mut contents = String::new();
     assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, templ_content);
 
     Ok(())
@@ -57,12 +57,368 @@ fn test_take_subdir() -> Result<(), Box<dyn Error>> {
     let file_path = Path::new("templ");
     let mut contents = String::new();
     assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, templ_content);
 
     Ok(())
 }
 
+#[test]
+#[serial]
+fn test_take_parent_notice() -> Result<(), Box<dyn Error>> {
+    let subdir = Path::new("sub").join("deeper");
+    let _t = Test::init(
+        "take_parent_notice",
+        vec![subdir.clone()],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "Template".to_string())]),
+        "touch",
+    );
+
+    // A template from a parent directory is reported
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(&subdir).arg("take");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let templ = env::current_dir()?.join(".templ.aar");
+    assert!(String::from_utf8(output.stderr)?
+        .contains(&format!("Using template {}\n", templ.display())));
+
+    // A template from the current directory is not
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)?.contains("Using template"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_auto_number() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".proj.aar")?;
+    let _t = Test::init(
+        "take_auto_number",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str(".standup.md.aar")?, "Standup".to_string()),
+            (templ_dir.join("README"), "Project".to_string()),
+            (PathBuf::from_str("standup-7.txt")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("standup")
+        .arg("--auto-number");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Creating standup-1.md\n"));
+    assert_eq!(fs::read_to_string("standup-1.md")?, "Standup");
+
+    // The highest existing number is incremented and padded
+    fs::rename("standup-1.md", "standup-4.md")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("standup")
+        .arg("--auto-number")
+        .arg("--width")
+        .arg("3");
+    cmd.assert().success();
+    assert!(Path::new("standup-005.md").is_file());
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("standup")
+        .arg("--auto-number");
+    cmd.assert().success();
+    assert!(Path::new("standup-6.md").is_file());
+
+    // Directory templates are numbered, too
+    for i in 1..=2 {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take")
+            .arg("-t")
+            .arg("proj")
+            .arg("--auto-number")
+            .write_stdin("y\n");
+        cmd.assert().success();
+        assert!(Path::new(&format!("proj-{i}")).join("README").is_file());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_count() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".proj.aar")?;
+    let _t = Test::init(
+        "take_count",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str(".sheet.csv.aar")?, "Sheet".to_string()),
+            (
+                PathBuf::from_str(".hello.aar")?,
+                "{{name}} {{who}}".to_string(),
+            ),
+            (templ_dir.join("README"), "Project".to_string()),
+        ]),
+        "false",
+    );
+
+    // The editor is not opened and all created paths are listed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("--count")
+        .arg("3");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Created 3 copies:\n"));
+    for i in 1..=3 {
+        assert!(stdout.contains(&format!("  sheet-{i}.csv\n")));
+        assert_eq!(fs::read_to_string(format!("sheet-{i}.csv"))?, "Sheet");
+    }
+
+    // Nothing is created if any of the copies exists
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("--count")
+        .arg("4")
+        .arg("--width")
+        .arg("2");
+    cmd.assert().success();
+    fs::write("grid-3.csv", "")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("grid.csv")
+        .arg("--count")
+        .arg("3");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("grid-3.csv"));
+    assert!(!Path::new("grid-1.csv").exists());
+    assert!(Path::new("sheet-04.csv").is_file());
+
+    // Each copy is opened in the editor with --edit-each
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("EDITOR", "sed -i s/Sheet/Edited/")
+        .arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("edited.csv")
+        .arg("--count")
+        .arg("2")
+        .arg("--edit-each");
+    cmd.assert().success();
+    for i in 1..=2 {
+        assert_eq!(fs::read_to_string(format!("edited-{i}.csv"))?, "Edited");
+    }
+
+    // Directory templates can be copied, too
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("proj")
+        .arg("--count")
+        .arg("2")
+        .write_stdin("y\ny\n");
+    cmd.assert().success();
+    for i in 1..=2 {
+        assert!(Path::new(&format!("proj-{i}")).join("README").is_file());
+    }
+
+    // Missing values are asked for once and shared by the copies
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("hello")
+        .arg("--count")
+        .arg("2")
+        .write_stdin("World\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?
+            .matches("Enter value of 'who'")
+            .count(),
+        1
+    );
+    for i in 1..=2 {
+        assert_eq!(
+            fs::read_to_string(format!("hello-{i}"))?,
+            format!("hello-{i} World")
+        );
+    }
+
+    // Neither a count of zero nor multiple names are accepted
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("--count")
+        .arg("0");
+    cmd.assert().failure();
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("sheet")
+        .arg("a.csv")
+        .arg("b.csv")
+        .arg("--count")
+        .arg("2");
+    cmd.assert().failure();
+    assert!(!Path::new("a-1.csv").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_hooks() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_hooks",
+        vec![PathBuf::from_str("src")?],
+        HashMap::from([
+            (
+                PathBuf::from_str(".module.rs.aar")?,
+                "---aar\nhooks = [\"echo hooked >> {target}\", \"pwd > cwd.txt\"]\n---\nmod m;\n"
+                    .to_string(),
+            ),
+            (
+                PathBuf::from_str(".failing.aar")?,
+                "---aar\nhooks = [\"exit 2\", \"touch after.txt\"]\n---\nFailing".to_string(),
+            ),
+            (
+                PathBuf::from_str(".fatal.aar")?,
+                "---aar\nhooks = [\"exit 2\", \"touch after.txt\"]\nhooks_fatal = true\n---\nFatal"
+                    .to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    // Hooks are run in the directory of the target
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("module").arg("src/my mod.rs");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("src/my mod.rs")?, "mod m;\nhooked\n");
+    let cwd = fs::read_to_string("src/cwd.txt")?;
+    assert!(cwd.trim_end().ends_with("src"));
+
+    // Hooks are skipped with --no-hooks
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("module")
+        .arg("other.rs")
+        .arg("--no-hooks");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("other.rs")?, "mod m;\n");
+    assert!(!Path::new("cwd.txt").exists());
+
+    // A failing hook is reported and the other hooks are run
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("failing");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?
+        .contains("Warning: Hook `exit 2` failed (exit status: 2)"));
+    assert!(Path::new("after.txt").is_file());
+
+    // A failing fatal hook stops take (but the target is kept)
+    fs::remove_file("after.txt")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("fatal");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)?.contains("Hook `exit 2` failed (exit status: 2)"));
+    assert!(!Path::new("after.txt").exists());
+    assert_eq!(fs::read_to_string("fatal")?, "Fatal");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_dated() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_dated",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".journal.md.aar")?, "Journal".to_string()),
+            (
+                PathBuf::from_str(".log.aar")?,
+                "---aar\ndated = \"%d.%m.%Y\"\n---\nLog".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    // The extension of the default name is kept
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_FAKE_NOW", "2024-03-05T10:00:00")
+        .arg("take")
+        .arg("--dated")
+        .arg("-t")
+        .arg("journal");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("2024-03-05.md")?, "Journal");
+
+    // An existing file is not overwritten
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_FAKE_NOW", "2024-03-05T10:00:00")
+        .arg("take")
+        .arg("--dated")
+        .arg("-t")
+        .arg("journal")
+        .env("TEMPLAAR_INTERACTIVE", "0");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("path already exists"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_FAKE_NOW", "2024-03-05T10:00:00")
+        .arg("take")
+        .arg("--dated=%Y/%m/%d")
+        .arg("-t")
+        .arg("journal");
+    cmd.assert().success();
+    assert!(Path::new("2024/03/05.md").is_file());
+
+    // The format may be given by the template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_FAKE_NOW", "2024-03-05T10:00:00")
+        .arg("take")
+        .arg("--dated")
+        .arg("-t")
+        .arg("log");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("05.03.2024")?, "Log");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_FAKE_NOW", "2024-03-05T10:00:00")
+        .arg("take")
+        .arg("--dated=%Q")
+        .arg("-t")
+        .arg("journal");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("invalid date format '%Q'"));
+
+    Ok(())
+}
+
 #[test]
 #[serial]
 fn test_take_named() -> Result<(), Box<dyn Error>> {
@@ -81,7 +437,7 @@ fn test_take_named() -> Result<(), Box<dyn Error>> {
     let file_path = Path::new("name");
     let mut contents = String::new();
     assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, templ_content);
 
     let default_file_path = Path::new("templ");
@@ -112,7 +468,7 @@ fn test_take_from_template() -> Result<(), Box<dyn Error>> {
     let file_path = Path::new("name");
     let mut contents = String::new();
     assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
     assert_eq!(contents, other_content);
 
     Ok(())
@@ -120,45 +476,208 @@ fn test_take_from_template() -> Result<(), Box<dyn Error>> {
 
 #[test]
 #[serial]
-fn test_take_global() -> Result<(), Box<dyn Error>> {
-    let home_dir = Path::new("home");
-    let config_dir = home_dir.join(".config").join("templaar");
-    let templ_content = "Template";
+fn test_take_git_check() -> Result<(), Box<dyn Error>> {
     let _t = Test::init(
-        "take_global",
-        vec![config_dir.to_path_buf()],
-        HashMap::from([(config_dir.join("templ.aar"), templ_content.to_string())]),
+        "take_git_check",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "new".to_string()),
+            (PathBuf::from_str(".gitignore")?, "ignored*\n".to_string()),
+            (PathBuf::from_str("tracked")?, "old".to_string()),
+        ]),
         "touch",
     );
-    env::set_var("HOME", env::current_dir()?.join(home_dir));
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+            .args(args)
+            .output()
+    };
+    // Skip the test if git is not available
+    if git(&["init", "-q", "."]).is_err() {
+        return Ok(());
+    }
+    git(&["add", "tracked", ".gitignore"])?;
+    git(&["commit", "-q", "-m", "init"])?;
+
+    // Declining (the default) leaves the tracked file untouched
+    for answer in ["y\nn\n", "y\n\n"] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").arg("tracked").write_stdin(answer);
+        let output = cmd.output()?;
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8(output.stdout)?.contains("continue? [y/N]"));
+        assert!(String::from_utf8(output.stderr)?.contains("tracked is tracked by git"));
+        assert_eq!(fs::read_to_string("tracked")?, "old");
+    }
 
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take").arg("-t").arg("templ");
+    cmd.arg("take").arg("ignored").write_stdin("n\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)?.contains("ignored is ignored by git"));
+    assert!(!Path::new("ignored").exists());
+
+    // The warnings are not printed with --quiet
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("ignored")
+        .arg("--quiet")
+        .write_stdin("n\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!String::from_utf8(output.stderr)?.contains("ignored by git"));
+
+    // Only a warning is printed when the user cannot be asked, with --yes, or
+    // when overwriting is requested explicitly
+    for args in [
+        vec!["ignored", "--non-interactive"],
+        vec!["--yes", "ignored-yes"],
+        vec!["tracked", "--on-conflict=overwrite"],
+    ] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").args(&args).write_stdin("");
+        let output = cmd.output()?;
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stderr)?.contains("by git"));
+    }
+    assert!(Path::new("ignored").exists());
+    assert!(Path::new("ignored-yes").exists());
+    assert_eq!(fs::read_to_string("tracked")?, "new");
+
+    // No check with --no-git-check
+    fs::write("tracked", "old")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("tracked")
+        .arg("--on-conflict=overwrite")
+        .arg("--no-git-check")
+        .write_stdin("y\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)?.contains("by git"));
+    assert_eq!(fs::read_to_string("tracked")?, "new");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_output_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".dir.aar")?;
+    let out_dir = PathBuf::from_str("out")?;
+    let _t = Test::init(
+        "take_output_dir",
+        vec![templ_dir.clone(), out_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "Template".to_string()),
+            (templ_dir.join("file"), "File".to_string()),
+            (out_dir.join("existing"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("target")
+        .arg("--output-dir")
+        .arg("out")
+        .write_stdin("y");
     cmd.assert().success();
+    assert_eq!(fs::read_to_string(out_dir.join("target"))?, "Template");
+    assert!(!Path::new("target").exists());
 
-    let file_path = Path::new("templ");
-    let mut contents = String::new();
-    assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
-    assert_eq!(contents, templ_content);
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("dir")
+        .arg("project")
+        .arg("-o")
+        .arg("out");
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(out_dir.join("project").join("file"))?,
+        "File"
+    );
+
+    // Conflicts are detected in the output directory
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("existing")
+        .arg("-o")
+        .arg("out")
+        .env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().failure();
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_global_precedence() -> Result<(), Box<dyn Error>> {
+fn test_take_dir_entry() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".meeting.aar")?;
+    let _t = Test::init(
+        "take_dir_entry",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("agenda"), "Agenda of {{name}}".to_string()),
+            (templ_dir.join("minutes"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("meeting:agenda")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("agenda")?, "Agenda of agenda");
+    assert!(!Path::new("minutes").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("notes.md")
+        .arg("-t")
+        .arg("meeting:agenda")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("notes.md")?, "Agenda of notes.md");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("meeting:missing");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No file matching 'missing'"));
+
+    // Exact template names take precedence
+    fs::write(".meeting:agenda.aar", "Exact")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("exact")
+        .arg("-t")
+        .arg("meeting:agenda")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("exact")?, "Exact");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_global() -> Result<(), Box<dyn Error>> {
     let home_dir = Path::new("home");
     let config_dir = home_dir.join(".config").join("templaar");
     let templ_content = "Template";
-    let other_content = "Other template";
     let _t = Test::init(
         "take_global",
         vec![config_dir.to_path_buf()],
-        HashMap::from([
-            (config_dir.join("templ.aar"), templ_content.to_string()),
-            (PathBuf::from_str(".templ.aar")?, other_content.to_string()),
-        ]),
+        HashMap::from([(config_dir.join("templ.aar"), templ_content.to_string())]),
         "touch",
     );
     env::set_var("HOME", env::current_dir()?.join(home_dir));
@@ -170,49 +689,97 @@ fn test_take_global_precedence() -> Result<(), Box<dyn Error>> {
     let file_path = Path::new("templ");
     let mut contents = String::new();
     assert!(file_path.exists());
-    fs::File::open(&file_path)?.read_to_string(&mut contents)?;
-    // Local template has precedence over the global one
-    assert_eq!(contents, other_content);
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_no_change() -> Result<(), Box<dyn Error>> {
-    let templ_content = "Template";
+fn test_take_global_single() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
     let _t = Test::init(
-        "take_no_change",
-        vec![],
-        HashMap::from([(PathBuf::from_str(".templ.aar")?, templ_content.to_string())]),
+        "take_global_single",
+        vec![config_dir.to_path_buf(), PathBuf::from_str("project")?],
+        HashMap::from([(config_dir.join("templ.aar"), "Template".to_string())]),
         "touch",
     );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
 
+    // The only global template is used without a name
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take").write_stdin("n");
+    cmd.current_dir("project").arg("take").arg("first");
     cmd.assert().success();
+    let mut contents = String::new();
+    fs::File::open("project/first")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Template");
 
-    let file_path = Path::new("templ");
-    assert!(!file_path.exists());
+    // Multiple global templates are ambiguous (once the last template is
+    // forgotten)
+    fs::write(config_dir.join("other.aar"), "Other")?;
+    Command::cargo_bin("templaar")?
+        .current_dir("project")
+        .arg("take")
+        .arg("--forget")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("project").arg("take").arg("second");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Ambiguous template"));
+    assert!(stderr.contains("templ") && stderr.contains("other"));
+
+    // No global templates
+    fs::remove_file(config_dir.join("templ.aar"))?;
+    fs::remove_file(config_dir.join("other.aar"))?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("project").arg("take").arg("third");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No template found"));
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_exists() -> Result<(), Box<dyn Error>> {
+fn test_take_scratch() -> Result<(), Box<dyn Error>> {
     let _t = Test::init(
-        "take_exists",
-        vec![],
-        HashMap::from([
-            (PathBuf::from_str(".templ.aar")?, String::new()),
-            (PathBuf::from_str("templ")?, String::new()),
-        ]),
+        "take_scratch",
+        vec!["home".into()],
+        HashMap::from([(PathBuf::from_str("existing.md")?, "Existing".to_string())]),
         "touch",
     );
+    env::set_var("HOME", env::current_dir()?.join("home"));
 
+    // Without --scratch, a missing template is an error
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take");
+    cmd.arg("take").arg("notes.md");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No template found"));
+    assert!(!Path::new("notes.md").exists());
+
+    // An empty file is created and opened in the editor
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("notes.md").arg("--scratch");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?
+        .contains("No template found, creating an empty file notes.md"));
+    assert!(Path::new("notes.md").is_file());
+
+    // Existing files are not overwritten and a name must be given
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("existing.md").arg("--scratch");
+    cmd.assert().failure();
+    assert_eq!(fs::read_to_string("existing.md")?, "Existing");
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--scratch");
     cmd.assert().failure();
 
     Ok(())
@@ -220,124 +787,3165 @@ fn test_take_exists() -> Result<(), Box<dyn Error>> {
 
 #[test]
 #[serial]
-fn test_take_ambiguous() -> Result<(), Box<dyn Error>> {
+fn test_take_search_root() -> Result<(), Box<dyn Error>> {
+    let subdir = Path::new("project").join("sub");
     let _t = Test::init(
-        "take_ambiguous",
+        "take_search_root",
         vec![
-            PathBuf::from_str(".templ.aar")?,
-            PathBuf::from_str(".note.aar")?,
+            subdir.clone(),
+            Path::new("project").join(".git"),
+            "home".into(),
         ],
-        HashMap::new(),
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "Template".to_string())]),
         "touch",
     );
+    env::set_var("HOME", env::current_dir()?.join("home"));
 
+    // The search stops at the directory containing .git
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take");
-    cmd.assert().failure();
+    cmd.current_dir(&subdir).arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("No template found"));
+    assert!(stderr.contains(&format!(
+        "up to {}",
+        env::current_dir()?.join("project").display()
+    )));
+
+    // The search root can be moved
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(&subdir)
+        .arg("take")
+        .arg("--search-root")
+        .arg(env::current_dir()?);
+    cmd.assert().success();
+    assert!(subdir.join("templ").exists());
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_from_dir() -> Result<(), Box<dyn Error>> {
-    let templ_dir = PathBuf::from_str(".templ.aar")?;
-    let file1_content = "Template";
-    let file2_content = "Other template";
-
+fn test_take_root_marker() -> Result<(), Box<dyn Error>> {
+    let subdir = Path::new("project").join("sub");
     let _t = Test::init(
-        "take_from_dir",
-        vec![templ_dir.clone()],
+        "take_root_marker",
+        vec![subdir.join(".templaar"), "home".into()],
         HashMap::from([
-            (templ_dir.join("file1"), file1_content.to_string()),
-            (templ_dir.join("file2"), file2_content.to_string()),
+            (PathBuf::from_str(".templ.aar")?, "Outer".to_string()),
+            (PathBuf::from_str("project/.root")?, String::new()),
         ]),
         "touch",
     );
+    env::set_var("HOME", env::current_dir()?.join("home"));
 
+    // The search stops at the directory containing .templaar
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take");
-    cmd.assert().success();
-
-    let target_path = Path::new("templ");
-    let file1_path = target_path.join("file1");
-    let file2_path = target_path.join("file2");
-    assert!(target_path.is_dir());
-    assert!(file1_path.is_file());
-    assert!(file2_path.is_file());
+    cmd.current_dir(&subdir).arg("take");
+    cmd.assert().failure();
 
-    let mut contents = String::new();
-    fs::File::open(&file1_path)?.read_to_string(&mut contents)?;
-    assert_eq!(contents, file1_content);
+    // A custom marker
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(&subdir)
+        .arg("take")
+        .arg("--root")
+        .arg(".root");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(&format!(
+        "up to {}",
+        env::current_dir()?.join("project").display()
+    )));
 
-    contents.clear();
-    fs::File::open(&file2_path)?.read_to_string(&mut contents)?;
-    assert_eq!(contents, file2_content);
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(&subdir)
+        .arg("take")
+        .arg("--root")
+        .arg(".missing");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(subdir.join("templ"))?, "Outer");
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_from_dir_into_nonempty_dir() -> Result<(), Box<dyn Error>> {
-    let templ_dir = PathBuf::from_str(".templ.aar")?;
-    let file1_content = "Template";
-    let file2_content = "Other template";
-
+fn test_take_show_shadowed() -> Result<(), Box<dyn Error>> {
+    let subdir = Path::new("testdir");
+    let _t = Test::init(
+        "take_show_shadowed",
+        vec![subdir.to_path_buf()],
+        HashMap::from([
+            (subdir.join(".a.aar"), "A".to_string()),
+            (PathBuf::from_str(".b.aar")?, "B".to_string()),
+        ]),
+        "touch",
+    );
+
+    // The nearest template is used silently by default
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(subdir).arg("take").arg("first");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)?.contains("shadows"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(subdir)
+        .arg("take")
+        .arg("second")
+        .arg("--show-shadowed");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(&format!(
+        "template {} shadows",
+        env::current_dir()?.join(subdir).join(".a.aar").display()
+    )));
+    assert!(stderr.contains(&format!(
+        "b ({})",
+        env::current_dir()?.join(".b.aar").display()
+    )));
+    assert_eq!(fs::read_to_string(subdir.join("second"))?, "A");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_shadowing_notice() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "take_shadowing_notice",
+        vec![config_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str(".report.aar")?, "Local".to_string()),
+            (config_dir.join("report.aar"), "Global".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("first").arg("-t").arg("report");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(&format!(
+        "using template {} which shadows {}",
+        env::current_dir()?.join(".report.aar").display(),
+        env::current_dir()?
+            .join(&config_dir)
+            .join("report.aar")
+            .display()
+    )));
+    assert_eq!(fs::read_to_string("first")?, "Local");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("-t")
+        .arg("report")
+        .arg("--quiet");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)?.contains("shadows"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_remember_last() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_remember_last",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".a.aar")?, "A".to_string()),
+            (PathBuf::from_str(".b.aar")?, "B".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("first");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Ambiguous template"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("second").arg("-t").arg("b");
+    cmd.assert().success();
+
+    // The last used template is preferred
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("third");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Using template b last used"));
+    assert_eq!(fs::read_to_string("third")?, "B");
+
+    // An explicit template updates the memory
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("fourth").arg("-t").arg("a");
+    cmd.assert().success();
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("fifth");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("fifth")?, "A");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--forget");
+    cmd.assert().success();
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("sixth");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_global_precedence() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let templ_content = "Template";
+    let other_content = "Other template";
+    let _t = Test::init(
+        "take_global",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (config_dir.join("templ.aar"), templ_content.to_string()),
+            (PathBuf::from_str(".templ.aar")?, other_content.to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("templ");
+    cmd.assert().success();
+
+    let file_path = Path::new("templ");
+    let mut contents = String::new();
+    assert!(file_path.exists());
+    fs::File::open(file_path)?.read_to_string(&mut contents)?;
+    // Local template has precedence over the global one
+    assert_eq!(contents, other_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_change() -> Result<(), Box<dyn Error>> {
+    let templ_content = "Template";
+    let _t = Test::init(
+        "take_no_change",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, templ_content.to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("n");
+    cmd.assert().code(3);
+
+    let file_path = Path::new("templ");
+    assert!(!file_path.exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_change_trailing_newline() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_no_change_trailing_newline",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "Template".to_string())]),
+        "sh -c 'echo >> \"$0\"'",
+    );
+
+    // The whitespace change is shown before asking
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?
+        .contains("-Template\n\\ No newline at end of file\n+Template\n"));
+
+    let file_path = Path::new("templ");
+    assert!(!file_path.exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_change_normalize() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_no_change_normalize",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "line \r\nother\r\n".to_string(),
+        )]),
+        "sed -i -e 's/ *\r$//'",
+    );
+
+    // Line endings and trailing whitespace count as changes by default
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout)?.contains("no change"));
+    assert_eq!(fs::read_to_string("templ")?, "line\nother\n");
+    fs::remove_file("templ")?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--normalize-compare")
+        .write_stdin("n\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("no change from the template"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_diff() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_diff",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "Author: {{author}}\n".to_string(),
+        )]),
+        "sh -c 'echo edited >> \"$0\"'",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--var").arg("author=me").arg("--diff");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("+++ "));
+    assert!(stdout.contains("@@ -1 +1,2 @@\n Author: me\n+edited\n"));
+
+    // Without --diff, changes are not printed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("other").arg("--var").arg("author=me");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout)?.contains("edited"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_binary() -> Result<(), Box<dyn Error>> {
+    let templ_content = "\u{0}PNG\u{1}";
+    let _t = Test::init(
+        "take_binary",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".image.aar")?, templ_content.to_string())]),
+        "sh -c 'echo edited > \"$0\"'",
+    );
+
+    // The editor is not opened and the user is not asked about saving the
+    // unchanged file
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("logo.png");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("logo.png")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_invalid_utf8() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".dir.aar")?;
+    let _t = Test::init(
+        "take_invalid_utf8",
+        vec![templ_dir.clone()],
+        HashMap::from([(templ_dir.join("README"), "# {{name}}".to_string())]),
+        "touch",
+    );
+    let binary = b"\x89PNG\xff\xfe";
+    fs::write(".logo.aar", binary)?;
+    fs::write(templ_dir.join("logo.png"), binary)?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("logo").arg("logo.png");
+    cmd.assert().success();
+    assert_eq!(fs::read("logo.png")?, binary);
+
+    // Unchanged files are compared as bytes after the editor is closed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("dir")
+        .arg("project")
+        .arg("--diff")
+        .write_stdin("y");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("no change from the template"));
+    assert_eq!(fs::read("project/logo.png")?, binary);
+    assert_eq!(fs::read_to_string("project/README")?, "# project");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_temp_files() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".dir.aar")?;
+    let _t = Test::init(
+        "take_no_temp_files",
+        vec![templ_dir.join("src")],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "Template".to_string()),
+            (templ_dir.join("src").join("main.rs"), "main".to_string()),
+            (PathBuf::from_str("existing")?, "original".to_string()),
+        ]),
+        "touch",
+    );
+
+    // Targets are written via temporary files which are renamed into place
+    for args in [
+        vec!["-t", "templ", "file"],
+        vec!["-t", "templ", "existing", "--on-conflict", "overwrite"],
+        vec!["-t", "dir", "project"],
+    ] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").args(args).write_stdin("y");
+        cmd.assert().success();
+    }
+    assert_eq!(fs::read_to_string("existing")?, "Template");
+    assert_eq!(fs::read_to_string("project/src/main.rs")?, "main");
+    for dir in [".", "project/src"] {
+        for entry in fs::read_dir(dir)? {
+            assert!(!entry?.file_name().to_string_lossy().contains("aartmp"));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_edit() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_no_edit",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "Template".to_string())]),
+        "sh -c 'echo edited > \"$0\"'",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("file.txt").arg("--no-edit");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("file.txt")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Template");
+
+    // No editor is needed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("other.txt")
+        .arg("--no-edit")
+        .env_remove("EDITOR");
+    cmd.assert().success();
+    assert!(Path::new("other.txt").is_file());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_missing_parent() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_missing_parent",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "Template".to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("src/module/foo.rs");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("src/module/foo.rs")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Template");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_verbose() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let _t = Test::init(
+        "take_verbose",
+        vec![templ_dir.clone(), PathBuf::from_str("sub")?],
+        HashMap::from([(templ_dir.join("README"), String::new())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("sub")
+        .arg("take")
+        .arg("project")
+        .arg("--verbose");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Searching for templates in") && stderr.contains("sub\n"));
+    assert!(stderr.contains("Found template") && stderr.contains(".templ.aar\n"));
+    assert!(stderr.contains("README -> ") && stderr.contains("project/README\n"));
+    assert!(stderr.contains("Created ") && stderr.contains(" (1 file(s) written)\n"));
+    assert!(!String::from_utf8(output.stdout)?.contains("Copying"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_templ_path() -> Result<(), Box<dyn Error>> {
+    let web_dir = PathBuf::from_str("shared/.web.aar")?;
+    let _t = Test::init(
+        "take_templ_path",
+        vec![web_dir.clone(), PathBuf::from_str("project")?],
+        HashMap::from([
+            (PathBuf::from_str("shared/.note.aar")?, "Note".to_string()),
+            (web_dir.join("index.html"), "Index".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("project")
+        .arg("take")
+        .arg("-t")
+        .arg("../shared/.note.aar");
+    cmd.assert().success();
+    let mut contents = String::new();
+    fs::File::open("project/note")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Note");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("project")
+        .arg("take")
+        .arg("site")
+        .arg("-t")
+        .arg("../shared/.web.aar");
+    cmd.assert().success();
+    assert!(Path::new("project/site/index.html").is_file());
+
+    // A missing path is an error (no search is done)
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir("project")
+        .arg("take")
+        .arg("-t")
+        .arg("../shared/.missing.aar");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains(".missing.aar does not exist"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_option() -> Result<(), Box<dyn Error>> {
+    let shared_dir = Path::new("shared");
+    let _t = Test::init(
+        "take_from_dir_option",
+        vec![shared_dir.to_path_buf()],
+        HashMap::from([
+            (PathBuf::from_str(".note.aar")?, "Local".to_string()),
+            (shared_dir.join("note.aar"), "Shared".to_string()),
+            (shared_dir.join("web.aar"), "Web".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("first")
+        .arg("--from-dir")
+        .arg("shared")
+        .arg("-t")
+        .arg("note");
+    cmd.assert().success();
+    let mut contents = String::new();
+    fs::File::open("first")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Shared");
+
+    // Ambiguity is handled as usual (once the last template is forgotten)
+    Command::cargo_bin("templaar")?
+        .arg("take")
+        .arg("--forget")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("--from-dir")
+        .arg("shared");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Ambiguous template"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("third")
+        .arg("--from-dir")
+        .arg("missing")
+        .arg("-t")
+        .arg("note");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Cannot read directory missing"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_prefix_glob() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_prefix_glob",
+        vec![],
+        HashMap::from([
+            (
+                PathBuf::from_str(".meeting-notes.aar")?,
+                "Notes".to_string(),
+            ),
+            (PathBuf::from_str(".bug-report.aar")?, "Report".to_string()),
+            (PathBuf::from_str(".bugfix.aar")?, "Fix".to_string()),
+            (PathBuf::from_str(".bug.aar")?, "Bug".to_string()),
+        ]),
+        "touch",
+    );
+
+    let take = |target: &str, templ: &str| -> Result<String, Box<dyn Error>> {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").arg(target).arg("-t").arg(templ);
+        cmd.assert().success();
+        let mut contents = String::new();
+        fs::File::open(target)?.read_to_string(&mut contents)?;
+        Ok(contents)
+    };
+    assert_eq!(take("first", "meet")?, "Notes");
+    assert_eq!(take("second", "bug-*")?, "Report");
+    // Exact match takes precedence
+    assert_eq!(take("third", "bug")?, "Bug");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("fourth").arg("-t").arg("bug?*");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Ambiguous template"));
+    assert!(stderr.contains("bug-report") && stderr.contains("bugfix"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("fifth")
+        .arg("-t")
+        .arg("meet")
+        .arg("--exact");
+    cmd.assert().failure();
+    assert!(!Path::new("fifth").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_glob_global() -> Result<(), Box<dyn Error>> {
+    let home_dir = Path::new("home");
+    let config_dir = home_dir.join(".config").join("templaar");
+    let _t = Test::init(
+        "take_glob_global",
+        vec![config_dir.join("rust")],
+        HashMap::from([
+            (
+                config_dir.join("rust").join("license.aar"),
+                "MIT".to_string(),
+            ),
+            (config_dir.join("rust").join("lib.aar"), "Lib".to_string()),
+            (config_dir.join("rust-bin.aar"), "Bin".to_string()),
+        ]),
+        "touch",
+    );
+    env::set_var("HOME", env::current_dir()?.join(home_dir));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("first").arg("-t").arg("rust-*");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("first")?, "Bin");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("second").arg("-t").arg("rust/lic*");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("second")?, "MIT");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("third").arg("-t").arg("rust/*");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Ambiguous template"));
+    assert!(stderr.contains("rust/license") && stderr.contains("rust/lib"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_ignore_case() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_ignore_case",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".meeting.aar")?, "Meeting".to_string()),
+            (PathBuf::from_str(".notes.aar")?, "notes".to_string()),
+            (PathBuf::from_str(".Notes.aar")?, "Notes".to_string()),
+        ]),
+        "touch",
+    );
+
+    // Exact matching by default
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("first").arg("-t").arg("Meeting");
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("first")
+        .arg("-t")
+        .arg("Meeting")
+        .arg("--ignore-case");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("first")?, "Meeting");
+
+    // Prefixes are matched ignoring case as well
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("-t")
+        .arg("MEET")
+        .arg("--ignore-case");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("second")?, "Meeting");
+
+    // Exact names take precedence
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("third")
+        .arg("-t")
+        .arg("Notes")
+        .arg("--ignore-case");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("third")?, "Notes");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("fourth")
+        .arg("-t")
+        .arg("NOTES")
+        .arg("--ignore-case")
+        .arg("--no-interactive");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Ambiguous template"));
+    assert!(stderr.contains("\"notes\"") && stderr.contains("\"Notes\""));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_exists() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_exists",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, String::new()),
+            (PathBuf::from_str("templ")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    // Without a terminal, the user is not asked to overwrite the target
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_exists_overwrite() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_exists_overwrite",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "Template".to_string()),
+            (PathBuf::from_str("templ")?, "original".to_string()),
+        ]),
+        "touch",
+    );
+
+    // The target is not overwritten by default
+    for answer in ["n\n", "\n"] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").write_stdin(answer);
+        let output = cmd.output()?;
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8(output.stdout)?.contains("overwrite? [y/N]"));
+        assert_eq!(fs::read_to_string("templ")?, "original");
+    }
+
+    // Declining to save the unchanged target restores the original file
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("y\nn\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("Save it anyways?"));
+    assert_eq!(fs::read_to_string("templ")?, "original");
+    assert!(!Path::new(".templ.aarbackup").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("y\n");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("templ")?, "Template");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_editor_failed() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_editor_failed",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "Template".to_string()),
+            (PathBuf::from_str("existing")?, "original".to_string()),
+        ]),
+        "false",
+    );
+
+    // The created file is deleted on request
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("d\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)?.contains("The editor failed"));
+    assert!(!Path::new("templ").exists());
+
+    // The edit can be retried and the file kept
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("r\nk\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout.matches("failed [r=retry, k=keep, d=delete]").count(),
+        2
+    );
+    assert_eq!(fs::read_to_string("templ")?, "Template");
+    fs::remove_file("templ")?;
+
+    // Without a terminal, the created file is removed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().failure().code(1);
+    assert!(!Path::new("templ").exists());
+
+    // An existing (overwritten) file is never deleted
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("existing")
+        .arg("--on-conflict")
+        .arg("overwrite")
+        .write_stdin("d\nk\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout)?.contains("d=delete"));
+    assert_eq!(fs::read_to_string("existing")?, "Template");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("existing")
+        .arg("--on-conflict")
+        .arg("overwrite")
+        .env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().failure().code(1);
+    assert!(Path::new("existing").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_template_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = Path::new("templates");
+    let _t = Test::init(
+        "take_template_dir",
+        vec![templ_dir.to_path_buf()],
+        HashMap::from([
+            (templ_dir.join(".note.aar"), "Note".to_string()),
+            (PathBuf::from_str(".note.aar")?, "Ignored".to_string()),
+        ]),
+        "touch",
+    );
+
+    // The template is searched in the given directory, the file is created
+    // in the current one
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--template-dir")
+        .arg("templates")
+        .arg("-t")
+        .arg("note")
+        .arg("--no-edit");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("note")?, "Note");
+    assert!(!templ_dir.join("note").exists());
+    fs::remove_file("note")?;
+
+    // The same using TEMPLAAR_DIR
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("note")
+        .arg("--no-edit")
+        .env("TEMPLAAR_DIR", "templates");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("note")?, "Note");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--template-dir").arg("missing");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)?.contains("missing: not a directory"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_non_interactive() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_non_interactive",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "Template".to_string()),
+            (PathBuf::from_str(".var.aar")?, "{{author}}".to_string()),
+        ]),
+        "touch",
+    );
+
+    // Without a terminal, the user is never asked and the unchanged file is
+    // kept
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .env_remove("TEMPLAAR_INTERACTIVE");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("templ")?, "Template");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("var")
+        .arg("--non-interactive")
+        .write_stdin("me\n");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Enter value of 'author'"));
+
+    // With --yes, the default answer is used
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("file")
+        .arg("--yes")
+        .env_remove("TEMPLAAR_INTERACTIVE");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("file")?, "Template");
+
+    // There is no default value of the variable
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("var").arg("--yes");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_ambiguous() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_ambiguous",
+        vec![
+            PathBuf::from_str(".templ.aar")?,
+            PathBuf::from_str(".note.aar")?,
+        ],
+        HashMap::new(),
+        "touch",
+    );
+
+    // Without a terminal, the user is not asked to choose
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .env("TEMPLAAR_INTERACTIVE", "0")
+        .write_stdin("1\n");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Ambiguous template"));
+
+    for flag in ["--no-interactive", "--non-interactive", "--yes"] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").arg(flag).write_stdin("1\n");
+        let output = cmd.output()?;
+        assert_eq!(output.status.code(), Some(1));
+        assert!(!String::from_utf8(output.stdout)?.contains("Choose template"));
+        assert!(String::from_utf8(output.stderr)?.contains("Ambiguous template"));
+    }
+
+    // Cancelling the choice declines
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("\n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("Choose template"));
+
+    // Otherwise, the user chooses the template (which is an empty directory
+    // template here)
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("note\n");
+    let output = cmd.output()?;
+    assert!(String::from_utf8(output.stderr)?.contains(".note.aar: directory template is empty"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let file1_content = "Template";
+    let file2_content = "Other template";
+
+    let _t = Test::init(
+        "take_from_dir",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), file1_content.to_string()),
+            (templ_dir.join("file2"), file2_content.to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("\n");
+    cmd.assert().success();
+
+    let target_path = Path::new("templ");
+    let file1_path = target_path.join("file1");
+    let file2_path = target_path.join("file2");
+    assert!(target_path.is_dir());
+    assert!(file1_path.is_file());
+    assert!(file2_path.is_file());
+
+    let mut contents = String::new();
+    fs::File::open(file1_path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, file1_content);
+
+    contents.clear();
+    fs::File::open(file2_path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, file2_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_into_nonempty_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let file1_content = "Template";
+    let file2_content = "Other template";
+
+    let _t = Test::init(
+        "take_from_dir",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), file1_content.to_string()),
+            (templ_dir.join("file2"), file2_content.to_string()),
+            (PathBuf::from_str("other_file")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").write_stdin("\n");
+    cmd.assert().success();
+
+    let file1_path = Path::new("file1");
+    let file2_path = Path::new("file2");
+    assert!(file1_path.is_file());
+    assert!(file2_path.is_file());
+
+    let mut contents = String::new();
+    fs::File::open(file1_path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, file1_content);
+
+    contents.clear();
+    fs::File::open(file2_path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, file2_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_conflict() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let file1_content = "Template";
+    let file2_content = "Other template";
+
+    let _t = Test::init(
+        "take_from_dir",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), file1_content.to_string()),
+            (templ_dir.join("file2"), file2_content.to_string()),
+            (PathBuf::from_str("file1")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".");
+    cmd.assert().failure();
+
+    assert!(!Path::new("file2").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_conflict_interactive() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_conflict_interactive",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), "new".to_string()),
+            (templ_dir.join("file2"), "new".to_string()),
+            (templ_dir.join("file3"), "new".to_string()),
+            (PathBuf::from_str("file1")?, "old".to_string()),
+            (PathBuf::from_str("file2")?, "old".to_string()),
+        ]),
+        "touch",
+    );
+
+    // Overwrite file1, skip file2
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").arg("-i").write_stdin("y\no\ns\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Skipped existing files:\n  file2\n"));
+
+    assert_eq!(fs::read_to_string("file1")?, "new");
+    assert_eq!(fs::read_to_string("file2")?, "old");
+    assert_eq!(fs::read_to_string("file3")?, "new");
+
+    // Abort leaves everything untouched
+    fs::write("file1", "old")?;
+    fs::remove_file("file3")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").arg("-i").write_stdin("y\na\n");
+    cmd.assert().failure();
+    assert_eq!(fs::read_to_string("file1")?, "old");
+    assert!(!Path::new("file3").exists());
+
+    // Overwrite all
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").arg("-i").write_stdin("y\nO\n");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("file1")?, "new");
+    assert_eq!(fs::read_to_string("file2")?, "new");
+    assert_eq!(fs::read_to_string("file3")?, "new");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_on_conflict() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_on_conflict",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), "new".to_string()),
+            (templ_dir.join("file2"), "new".to_string()),
+            (PathBuf::from_str("file1")?, "old".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(".")
+        .arg("--on-conflict=abort")
+        .write_stdin("y");
+    cmd.assert().failure();
+    assert!(!Path::new("file2").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(".")
+        .arg("--on-conflict=skip")
+        .write_stdin("y");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("1 copied, 1 skipped, 0 overwritten"));
+    assert_eq!(fs::read_to_string("file1")?, "old");
+    assert_eq!(fs::read_to_string("file2")?, "new");
+
+    fs::remove_file("file2")?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(".")
+        .arg("--on-conflict=overwrite")
+        .write_stdin("y");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("1 copied, 0 skipped, 1 overwritten"));
+    assert_eq!(fs::read_to_string("file1")?, "new");
+    assert_eq!(fs::read_to_string("file2")?, "new");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_quiet() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_quiet",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("file1"), "new".to_string()),
+            (templ_dir.join("file2"), "new".to_string()),
+            (PathBuf::from_str("file1")?, "old".to_string()),
+        ]),
+        "touch",
+    );
+
+    // Neither the questions answered by --yes nor the summary are printed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(".")
+        .arg("--on-conflict=skip")
+        .arg("--quiet")
+        .arg("--yes");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert_eq!(fs::read_to_string("file1")?, "old");
+    assert_eq!(fs::read_to_string("file2")?, "new");
+
+    // Errors are still printed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").arg("--quiet").arg("--yes");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.starts_with("Error:"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_on_conflict() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_on_conflict",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "new".to_string()),
+            (PathBuf::from_str("target")?, "old".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("target").arg("--on-conflict=skip");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("target")?, "old");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("target")
+        .arg("--on-conflict=overwrite")
+        .write_stdin("n");
+    assert_eq!(cmd.output()?.status.code(), Some(3));
+    assert_eq!(fs::read_to_string("target")?, "old");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("target")
+        .arg("--on-conflict=overwrite")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("target")?, "new");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_flatten() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let other_dir = PathBuf::from_str(".other.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_flatten",
+        vec![
+            templ_dir.join("docs").join("nested"),
+            templ_dir.join("empty"),
+            other_dir.join("a"),
+            other_dir.join("b"),
+        ],
+        HashMap::from([
+            (templ_dir.join("README"), String::new()),
+            (templ_dir.join("docs").join("guide.md"), String::new()),
+            (
+                templ_dir.join("docs").join("nested").join("{{name}}.md"),
+                String::new(),
+            ),
+            (other_dir.join("a").join("mod.rs"), String::new()),
+            (other_dir.join("b").join("mod.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("project")
+        .arg("--flatten");
+    cmd.assert().success();
+    let mut entries = fs::read_dir("project")?
+        .map(|e| Ok(e?.file_name().to_string_lossy().to_string()))
+        .collect::<Result<Vec<String>, std::io::Error>>()?;
+    entries.sort();
+    assert_eq!(entries, vec!["README", "guide.md", "project.md"]);
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("other")
+        .arg("collision")
+        .arg("--flatten");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("a/mod.rs and b/mod.rs are both copied to mod.rs"));
+    assert!(!Path::new("collision").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_only() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_only",
+        vec![templ_dir.join("src"), PathBuf::from_str("first/src")?],
+        HashMap::from([
+            (templ_dir.join("Makefile"), String::new()),
+            (templ_dir.join("README"), String::new()),
+            (templ_dir.join("notes.md"), String::new()),
+            (templ_dir.join("src").join("main.rs"), String::new()),
+            (templ_dir.join("src").join("lib.md"), String::new()),
+            (PathBuf::from_str("first/src/main.rs")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    // Conflicting files which are not selected are ignored
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("first")
+        .arg("--only")
+        .arg("Makefile,README")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert!(Path::new("first/Makefile").is_file());
+    assert!(Path::new("first/README").is_file());
+    assert!(!Path::new("first/notes.md").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("--only")
+        .arg("*.md")
+        .arg("--only")
+        .arg("src");
+    cmd.assert().success();
+    assert!(Path::new("second/notes.md").is_file());
+    assert!(Path::new("second/src/main.rs").is_file());
+    assert!(Path::new("second/src/lib.md").is_file());
+    assert!(!Path::new("second/Makefile").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("third")
+        .arg("--only")
+        .arg("Makefile,missing");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No file matching 'missing'"));
+    assert!(!Path::new("third").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_confirm_create() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".project.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_confirm_create",
+        vec![templ_dir.clone()],
+        HashMap::from([(templ_dir.join("README"), String::new())]),
+        "touch",
+    );
+
+    // Declining does not create anything
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("from template project?"));
+    assert!(!Path::new("project").exists());
+
+    // The end of input is not taken as the default answer
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("");
+    cmd.assert().failure().code(3);
+    assert!(!Path::new("project").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("y");
+    cmd.assert().success();
+    assert!(Path::new("project/README").is_file());
+
+    // No confirmation if the name is given explicitly
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("other");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout)?.contains("Create directory"));
+    assert!(Path::new("other/README").is_file());
+
+    // Nor when running non-interactively or with --yes
+    for (dir, args) in [
+        ("non-interactive", vec!["--non-interactive"]),
+        ("yes", vec!["--yes"]),
+    ] {
+        fs::create_dir(dir)?;
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.current_dir(dir)
+            .arg("take")
+            .arg("-t")
+            .arg("project")
+            .args(args);
+        let output = cmd.output()?;
+        assert!(output.status.success());
+        assert!(!String::from_utf8(output.stdout)?.contains("Create directory"));
+        assert!(Path::new(dir).join("project/README").is_file());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_rename() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_rename",
+        vec![templ_dir.join("css"), PathBuf::from_str("third")?],
+        HashMap::from([
+            (
+                templ_dir.join("index.html"),
+                "<h1>{{name}}</h1>".to_string(),
+            ),
+            (templ_dir.join("css").join("style.css"), String::new()),
+            (PathBuf::from_str("third/about.html")?, "About".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("first")
+        .arg("--rename")
+        .arg("index.html=about.html")
+        .arg("--rename")
+        .arg("css/style.css=about.css");
+    cmd.assert().success();
+    let mut contents = String::new();
+    fs::File::open("first/about.html")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "<h1>first</h1>");
+    assert!(Path::new("first/about.css").is_file());
+    assert!(!Path::new("first/index.html").exists());
+    assert!(!Path::new("first/css/style.css").exists());
+
+    // Renaming two files to the same destination
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("--rename")
+        .arg("index.html=page")
+        .arg("--rename")
+        .arg("css/style.css=page");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("are both copied to page"));
+
+    // Renamed file which is not in the template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("second")
+        .arg("--rename")
+        .arg("missing.html=about.html");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No file matching 'missing.html'"));
+
+    // Conflicts are detected using the new names
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("third")
+        .arg("--rename")
+        .arg("index.html=about.html")
+        .write_stdin("y");
+    cmd.assert().failure();
+    contents.clear();
+    fs::File::open("third/about.html")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "About");
+    assert!(!Path::new("third/css").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_rollback() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_rollback",
+        vec![templ_dir.join("src"), PathBuf::from_str("existing")?],
+        HashMap::from([
+            (templ_dir.join("README"), "readme".to_string()),
+            (
+                templ_dir.join("src").join("main.rs"),
+                "{{date:%Q}}".to_string(),
+            ),
+            (
+                PathBuf::from_str("existing/README")?,
+                "original".to_string(),
+            ),
+            (PathBuf::from_str("existing/notes")?, "notes".to_string()),
+        ]),
+        "touch",
+    );
+
+    // A new target directory is removed completely
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("created");
+    cmd.assert().failure();
+    assert!(!Path::new("created").exists());
+
+    // Only created files are removed, overwritten files are restored
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("existing")
+        .arg("--on-conflict")
+        .arg("overwrite")
+        .write_stdin("y");
+    cmd.assert().failure();
+    let mut entries: Vec<_> = fs::read_dir("existing")?
+        .map(|e| e.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    assert_eq!(entries, vec!["README", "notes"]);
+    let mut contents = String::new();
+    fs::File::open("existing/README")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "original");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_no_change() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_no_change",
+        vec![templ_dir.join("src"), PathBuf::from_str("existing")?],
+        HashMap::from([
+            (templ_dir.join("README"), "# {{name}}".to_string()),
+            (templ_dir.join("src").join("main.rs"), String::new()),
+            (
+                PathBuf::from_str("existing/README")?,
+                "original".to_string(),
+            ),
+            (PathBuf::from_str("existing/notes")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("removed").write_stdin("n");
+    let output = cmd.output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stdout)?.contains("no change from the template"));
+    assert!(!Path::new("removed").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("kept").write_stdin("y");
+    cmd.assert().success();
+    assert!(Path::new("kept/README").is_file());
+    assert!(Path::new("kept/src/main.rs").is_file());
+
+    // Files which existed before are never removed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("existing")
+        .arg("--on-conflict")
+        .arg("skip")
+        .write_stdin("y\nn");
+    cmd.assert().code(3);
+    let mut entries: Vec<_> = fs::read_dir("existing")?
+        .map(|e| e.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+    assert_eq!(entries, vec!["README", "notes"]);
+    let mut contents = String::new();
+    fs::File::open("existing/README")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "original");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_empty() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_from_dir_empty",
+        vec![PathBuf::from_str(".templ.aar")?],
+        HashMap::new(),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("project");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("directory template is empty"));
+    assert!(!Path::new("project").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_target_in_templ() -> Result<(), Box<dyn Error>> {
+    let templ_dir = Path::new(".proj.aar");
+    let _t = Test::init(
+        "take_from_dir_target_in_templ",
+        vec![templ_dir.to_path_buf()],
+        HashMap::from([(templ_dir.join("file"), "File".to_string())]),
+        "touch",
+    );
+
+    // Running take inside the template directory
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.current_dir(templ_dir).arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("inside of template"));
+
+    // The target name points into the template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(templ_dir.join("inner"))
+        .arg("-t")
+        .arg("proj");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("inside of template"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg(".")
+        .arg("-t")
+        .arg("proj")
+        .arg("-o")
+        .arg(templ_dir);
+    cmd.assert().failure();
+
+    assert_eq!(fs::read_dir(templ_dir)?.count(), 1);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_target_is_templ() -> Result<(), Box<dyn Error>> {
+    let templ_dir = Path::new(".proj.aar");
+    let _t = Test::init(
+        "take_target_is_templ",
+        vec![templ_dir.to_path_buf(), "proj".into()],
+        HashMap::from([
+            (PathBuf::from_str(".note.aar")?, "Note".to_string()),
+            (templ_dir.join("file"), "File".to_string()),
+        ]),
+        "touch",
+    );
+    std::os::unix::fs::symlink(".note.aar", "note")?;
+    std::os::unix::fs::symlink("../.proj.aar/file", "proj/file")?;
+
+    // File template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("note")
+        .arg("--on-conflict=overwrite");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("they are the same file"));
+    assert_eq!(fs::read_to_string(".note.aar")?, "Note");
+
+    // File of a directory template
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("proj")
+        .arg("-t")
+        .arg("proj")
+        .arg("--on-conflict=overwrite")
+        .write_stdin("\n");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("they are the same file"));
+    assert_eq!(fs::read_to_string(templ_dir.join("file"))?, "File");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_nested() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let workflows_dir = templ_dir.join(".github").join("workflows");
+    let src_dir = templ_dir.join("{{name}}_src");
+
+    let _t = Test::init(
+        "take_from_dir_nested",
+        vec![workflows_dir.clone(), src_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("README.md"), "# {{name}}".to_string()),
+            (workflows_dir.join("ci.yml"), "name: CI".to_string()),
+            (src_dir.join("main.rs"), "fn main() {}".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("project");
+    cmd.assert().success();
+
+    let target_path = Path::new("project");
+    let mut contents = String::new();
+    fs::File::open(target_path.join("README.md"))?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "# project");
+
+    contents.clear();
+    fs::File::open(target_path.join(".github").join("workflows").join("ci.yml"))?
+        .read_to_string(&mut contents)?;
+    assert_eq!(contents, "name: CI");
+
+    contents.clear();
+    fs::File::open(target_path.join("project_src").join("main.rs"))?
+        .read_to_string(&mut contents)?;
+    assert_eq!(contents, "fn main() {}");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_nested_conflict() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let src_dir = PathBuf::from_str("src")?;
+
+    let _t = Test::init(
+        "take_from_dir_nested_conflict",
+        vec![templ_dir.join(&src_dir), src_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("README.md"), String::new()),
+            (templ_dir.join(&src_dir).join("main.rs"), String::new()),
+            (src_dir.join("main.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").write_stdin("y");
+    cmd.assert().failure();
+
+    assert!(!Path::new("README.md").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_nested_template() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_nested_template",
+        vec![templ_dir.join("sub")],
+        HashMap::from([
+            (templ_dir.join("file"), String::new()),
+            (templ_dir.join("sub").join(".other.aar"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("cannot contain other templates"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_ignore() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_ignore",
+        vec![templ_dir.join("build"), templ_dir.join("src")],
+        HashMap::from([
+            (
+                templ_dir.join(".templaarignore"),
+                ".DS_Store\nbuild/\n".to_string(),
+            ),
+            (templ_dir.join(".DS_Store"), String::new()),
+            (templ_dir.join("build").join("out"), String::new()),
+            (templ_dir.join("src").join(".DS_Store"), String::new()),
+            (templ_dir.join("src").join("main.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("project");
+    cmd.assert().success();
+
+    let target_path = Path::new("project");
+    assert!(target_path.join("src").join("main.rs").is_file());
+    assert!(!target_path.join(".templaarignore").exists());
+    assert!(!target_path.join(".DS_Store").exists());
+    assert!(!target_path.join("src").join(".DS_Store").exists());
+    assert!(!target_path.join("build").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_aarignore() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_aarignore",
+        vec![templ_dir.join("target"), templ_dir.join("src")],
+        HashMap::from([
+            (
+                templ_dir.join(".aarignore"),
+                "*.swp\n!keep.swp\ntarget/\n".to_string(),
+            ),
+            (templ_dir.join("target").join("out"), String::new()),
+            (templ_dir.join("src").join("main.rs.swp"), String::new()),
+            (templ_dir.join("src").join("keep.swp"), String::new()),
+            (templ_dir.join("src").join("main.rs"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("project");
+    cmd.assert().success();
+
+    let target_path = Path::new("project");
+    assert!(target_path.join("src").join("main.rs").is_file());
+    assert!(target_path.join("src").join("keep.swp").is_file());
+    assert!(!target_path.join("src").join("main.rs.swp").exists());
+    assert!(!target_path.join("target").exists());
+    assert!(!target_path.join(".aarignore").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_empty_dirs() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_empty_dirs",
+        vec![
+            templ_dir.join("logs"),
+            templ_dir.join("build"),
+            templ_dir.join("{{name}}_data"),
+        ],
+        HashMap::from([
+            (templ_dir.join("README.md"), String::new()),
+            (templ_dir.join("build").join(".aarkeep"), String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("project");
+    cmd.assert().success();
+
+    let target_path = Path::new("project");
+    assert!(target_path.join("README.md").is_file());
+    assert!(target_path.join("logs").is_dir());
+    assert!(target_path.join("build").is_dir());
+    assert!(target_path.join("project_data").is_dir());
+    assert!(!target_path.join("build").join(".aarkeep").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_take_from_dir_symlinks() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_symlinks",
+        vec![templ_dir.clone()],
+        HashMap::from([(templ_dir.join("v2.conf"), "v2".to_string())]),
+        "touch",
+    );
+    std::os::unix::fs::symlink("v2.conf", templ_dir.join("latest.conf"))?;
+    std::os::unix::fs::symlink("missing.conf", templ_dir.join("broken.conf"))?;
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("links");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("broken symbolic link"));
+
+    let target_path = Path::new("links");
+    assert_eq!(
+        fs::read_link(target_path.join("latest.conf"))?,
+        Path::new("v2.conf")
+    );
+    assert_eq!(
+        fs::read_link(target_path.join("broken.conf"))?,
+        Path::new("missing.conf")
+    );
+
+    // With --dereference, the linked file is copied
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("copies").arg("--dereference");
+    cmd.assert().success();
+
+    let target_path = Path::new("copies");
+    assert!(!target_path.join("latest.conf").is_symlink());
+    assert_eq!(fs::read_to_string(target_path.join("latest.conf"))?, "v2");
+    assert!(target_path.join("broken.conf").is_symlink());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_take_from_dir_symlink_to_dir() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+
+    let _t = Test::init(
+        "take_from_dir_symlink_to_dir",
+        vec![templ_dir.join("v2")],
+        HashMap::from([(templ_dir.join("v2").join("file"), "v2".to_string())]),
+        "touch",
+    );
+    std::os::unix::fs::symlink("v2", templ_dir.join("latest"))?;
+
+    // Links to directories are recreated even with --dereference
+    for args in [vec!["links"], vec!["copies", "--dereference"]] {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take").args(&args);
+        cmd.assert().success();
+
+        let target_path = Path::new(args[0]);
+        assert_eq!(fs::read_link(target_path.join("latest"))?, Path::new("v2"));
+        assert!(!target_path.join("v2").is_symlink());
+        assert_eq!(fs::read_to_string(target_path.join("latest/file"))?, "v2");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_take_preserve_mode() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let templ_dir = PathBuf::from_str(".dir.aar")?;
+    let _t = Test::init(
+        "take_preserve_mode",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (PathBuf::from_str(".script.aar")?, "#!/bin/sh".to_string()),
+            (templ_dir.join("run.sh"), "#!/bin/sh".to_string()),
+        ]),
+        "touch",
+    );
+    for file in [Path::new(".script.aar"), &templ_dir.join("run.sh")] {
+        fs::set_permissions(file, fs::Permissions::from_mode(0o755))?;
+    }
+    let mode = |path: &Path| -> Result<u32, Box<dyn Error>> {
+        Ok(fs::metadata(path)?.permissions().mode() & 0o777)
+    };
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("script")
+        .arg("run.sh")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(mode(Path::new("run.sh"))?, 0o755);
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("dir").arg("project");
+    cmd.assert().success();
+    assert_eq!(mode(&Path::new("project").join("run.sh"))?, 0o755);
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("script")
+        .arg("plain")
+        .arg("--no-preserve-mode")
+        .write_stdin("y");
+    cmd.assert().success();
+    assert_eq!(mode(Path::new("plain"))? & 0o111, 0);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn test_take_wait() -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _t = Test::init(
+        "take_wait",
+        vec![PathBuf::from_str("bin")?, PathBuf::from_str(".proj.aar")?],
+        HashMap::from([
+            (PathBuf::from_str(".note.aar")?, "Note".to_string()),
+            (PathBuf::from_str(".proj.aar/README")?, "Readme".to_string()),
+            (
+                PathBuf::from_str("bin/code")?,
+                "#!/bin/sh\necho \"$@\" > args.txt".to_string(),
+            ),
+            (PathBuf::from_str("bin/vim")?, "#!/bin/sh".to_string()),
+        ]),
+        "touch",
+    );
+    for editor in ["bin/code", "bin/vim"] {
+        fs::set_permissions(editor, fs::Permissions::from_mode(0o755))?;
+    }
+
+    // The wait argument is added for known editors (only once)
+    for (i, editor) in ["bin/code", "bin/code --wait"].iter().enumerate() {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take")
+            .arg("-t")
+            .arg("note")
+            .arg(format!("code-{i}"))
+            .arg("--editor")
+            .arg(editor)
+            .arg("--wait");
+        cmd.assert().success();
+        let args = fs::read_to_string("args.txt")?;
+        assert!(args.starts_with("--wait ") && args.ends_with(&format!("code-{i}\n")));
+        assert_eq!(args.matches("--wait").count(), 1);
+    }
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("note")
+        .arg("no-wait")
+        .arg("--editor")
+        .arg("bin/code");
+    cmd.assert().success();
+    assert!(!fs::read_to_string("args.txt")?.contains("--wait"));
+
+    // Other editors are waited for until the user confirms
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("note")
+        .arg("confirmed")
+        .arg("--editor")
+        .arg("true")
+        .arg("--wait")
+        .write_stdin("\n");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Press Enter when you are done editing"));
+
+    // When running non-interactively, until the file is changed
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_INTERACTIVE", "0")
+        .arg("take")
+        .arg("-t")
+        .arg("note")
+        .arg("polled")
+        .arg("--editor")
+        .arg("sh -c '(sleep 1; echo edited >> \"$0\") &'")
+        .arg("--wait");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("polled")?, "Noteedited\n");
+
+    // Directories are not polled
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.env("TEMPLAAR_INTERACTIVE", "0")
+        .arg("take")
+        .arg("-t")
+        .arg("proj")
+        .arg("proj")
+        .arg("--editor")
+        .arg("true")
+        .arg("--wait");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Not waiting for the editor of directory"));
+
+    // Terminal editors and editors which did not return immediately are not
+    // waited for
+    for (i, editor) in ["bin/vim", "sh -c 'sleep 2.5'"].iter().enumerate() {
+        let mut cmd = Command::cargo_bin("templaar")?;
+        cmd.arg("take")
+            .arg("-t")
+            .arg("note")
+            .arg(format!("blocking-{i}"))
+            .arg("--editor")
+            .arg(editor)
+            .arg("--wait");
+        let output = cmd.output()?;
+        assert!(output.status.success());
+        assert!(!String::from_utf8(output.stdout)?.contains("Press Enter"));
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_env() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_env",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "Author: {{env:TEMPLAAR_AUTHOR}}, project: {{env:TEMPLAAR_PROJECT:none}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").env("TEMPLAAR_AUTHOR", "me");
+    cmd.env_remove("TEMPLAAR_PROJECT");
+    cmd.write_stdin("y").assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Author: me, project: none");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_env_var_precedence() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_env_var_precedence",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "Author: {{env:TEMPLAAR_AUTHOR}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--var")
+        .arg("TEMPLAAR_AUTHOR=you")
+        .env("TEMPLAAR_AUTHOR", "me");
+    cmd.write_stdin("y").assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Author: you");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_env_undefined() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_env_undefined",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "Author: {{env:TEMPLAAR_AUTHOR}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").env_remove("TEMPLAAR_AUTHOR");
+    cmd.assert().failure();
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_env() -> Result<(), Box<dyn Error>> {
+    let templ_content = "Author: {{env:TEMPLAAR_AUTHOR}}";
+    let _t = Test::init(
+        "take_no_env",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, templ_content.to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--no-env").env("TEMPLAAR_AUTHOR", "me");
+    cmd.write_stdin("y").assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_date() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_date",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "{{date}}\n{{date:%A %d %B %Y, %H:%M}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .env("TEMPLAAR_FAKE_NOW", "2024-03-01T09:30:00")
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "2024-03-01\nFriday 01 March 2024, 09:30");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_date_invalid_format() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_date_invalid_format",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, "{{date:%Q}}".to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    cmd.assert().failure();
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_var_default() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_var_default",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "{{reviewer:nobody}} {{author:me}} [{{empty:}}] {{url:http://localhost:8080}}"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--var")
+        .arg("author=you")
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "nobody you [] http://localhost:8080");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_set() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_set",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "{{name}} {{date}} {{title}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    // --set is an alias of --var, usable without a terminal
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("out")
+        .arg("--set")
+        .arg("name=Alice")
+        .arg("--set")
+        .arg("date=2024-01-01")
+        .arg("--var")
+        .arg("title=Notes")
+        .arg("--no-edit")
+        .env("TEMPLAAR_INTERACTIVE", "0");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("out")?, "Alice 2024-01-01 Notes");
+
+    // Values which are not set are left as they are with --no-edit
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("other")
+        .arg("--set")
+        .arg("name=Alice")
+        .arg("--no-edit")
+        .write_stdin("Unused\n");
+    cmd.assert().success();
+    let contents = fs::read_to_string("other")?;
+    assert!(contents.starts_with("Alice ") && contents.ends_with(" {{title}}"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_var_prompt() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_var_prompt",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "{{author}}, {{author}}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("me\n");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "me, me");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_vars_file() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_vars_file",
+        vec![],
+        HashMap::from([
+            (
+                PathBuf::from_str(".templ.aar")?,
+                "{{author}} {{reviewer}} {{count}} {{draft}} {{title}}".to_string(),
+            ),
+            (
+                PathBuf::from_str("vars.toml")?,
+                "author = \"me\"\nreviewer = \"nobody\"\ncount = 3\ndraft = true\n".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--vars-file")
+        .arg("vars.toml")
+        .arg("--var")
+        .arg("reviewer=you")
+        .write_stdin("Title\n");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "me you 3 true Title");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_vars_sidecar() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_vars_sidecar",
+        vec![],
+        HashMap::from([
+            (
+                PathBuf::from_str(".templ.aar")?,
+                "{{author}} {{team:none}} {{title}}".to_string(),
+            ),
+            (
+                PathBuf::from_str(".templ.aar.vars")?,
+                "# Defaults\nauthor=me\n\nteam=core\n".to_string(),
+            ),
+            (PathBuf::from_str(".bad.aar")?, "{{x}}".to_string()),
+            (PathBuf::from_str(".bad.aar.vars")?, "x\n".to_string()),
+        ]),
+        "touch",
+    );
+
+    // The sidecar is not a template itself
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--names-only").arg("--local");
+    let output = cmd.output()?;
+    assert_eq!(String::from_utf8(output.stdout)?, "bad\ntempl\n");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("templ").arg("--list-vars");
+    let output = cmd.output()?;
+    assert!(String::from_utf8(output.stdout)?.contains("(default: core)"));
+
+    // Values from the sidecar can be overridden
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .arg("--var")
+        .arg("team=web")
+        .write_stdin("Title\n");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("templ")?, "me web Title");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("bad");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains(".bad.aar.vars: line 1: invalid variable"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_vars_file_json_yaml() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_vars_file_json_yaml",
+        vec![],
+        HashMap::from([
+            (
+                PathBuf::from_str(".templ.aar")?,
+                "{{author}} {{count}}".to_string(),
+            ),
+            (
+                PathBuf::from_str("vars.json")?,
+                "{\"author\": \"me\", \"count\": 3}".to_string(),
+            ),
+            (
+                PathBuf::from_str("vars.yaml")?,
+                "author: you\ncount: 4\n".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("json")
+        .arg("--vars-file")
+        .arg("vars.json");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("yaml")
+        .arg("--vars-file")
+        .arg("vars.yaml");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("json")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "me 3");
+
+    contents.clear();
+    fs::File::open("yaml")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "you 4");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_vars_file_invalid() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_vars_file_invalid",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".templ.aar")?, "{{author}}".to_string()),
+            (
+                PathBuf::from_str("vars.toml")?,
+                "author = [\"me\"]\n".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--vars-file").arg("vars.toml");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("'author'"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_global_xdg() -> Result<(), Box<dyn Error>> {
+    let config_dir = Path::new("config").join("templaar");
+    let templ_content = "Template";
+    let _t = Test::init(
+        "take_global_xdg",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([(config_dir.join("templ.aar"), templ_content.to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("-t")
+        .arg("templ")
+        .env("XDG_CONFIG_HOME", env::current_dir()?.join("config"))
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_list_vars() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let _t = Test::init(
+        "take_list_vars",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (
+                templ_dir.join("file1"),
+                "{{author}} {{reviewer:nobody}} {{date}}".to_string(),
+            ),
+            (
+                templ_dir.join("file2"),
+                "{{author}} {{env:USER}}".to_string(),
+            ),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--list-vars");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "author                       file1, file2\n\
+         reviewer  (default: nobody)  file1\n\
+         env:USER                     file2\n"
+    );
+    assert!(!Path::new("templ").exists());
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--list-vars").arg("--json");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("\"name\": \"reviewer\""));
+    assert!(stdout.contains("\"default\": \"nobody\""));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_escape() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_escape",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "{{name}}: \\{{ .Values.name }}".to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--var").arg("name=chart");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "chart: {{ .Values.name }}");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_raw() -> Result<(), Box<dyn Error>> {
+    let templ_content = "replicaCount: {{ .Values.replicas }}\n\
+                         image: \"{{ .Values.image }}:{{ .Chart.AppVersion }}\"\n\
+                         user: {{env:USER}}\n\
+                         escaped: \\{{\n";
     let _t = Test::init(
-        "take_from_dir",
+        "take_raw",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, templ_content.to_string())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("--raw").write_stdin("y");
+    cmd.assert().success();
+
+    assert_eq!(fs::read("templ")?, templ_content.as_bytes());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_no_editor() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_no_editor",
+        vec![],
+        HashMap::from([(PathBuf::from_str(".templ.aar")?, String::new())]),
+        "touch",
+    );
+    env::remove_var("EDITOR");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Set $EDITOR or use the --editor option"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_frontmatter() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_frontmatter",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "---aar\n\
+             description = \"Meeting notes\"\n\
+             target = \"notes.md\"\n\
+             [vars]\n\
+             author = \"me\"\n\
+             ---\n\
+             # Notes by {{author}}\n"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("notes.md")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "# Notes by me\n");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_frontmatter_malformed() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_frontmatter_malformed",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "---aar\n\
+             description = \"Meeting notes\"\n\
+             target = \n\
+             ---\n\
+             Notes\n"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("line 3"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_placeholder_names() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let _t = Test::init(
+        "take_from_dir_placeholder_names",
         vec![templ_dir.clone()],
         HashMap::from([
-            (templ_dir.join("file1"), file1_content.to_string()),
-            (templ_dir.join("file2"), file2_content.to_string()),
-            (PathBuf::from_str("other_file")?, String::new()),
+            (templ_dir.join("{{name}}.rs"), "mod {{name}};".to_string()),
+            (templ_dir.join("{{name}}_test.rs"), String::new()),
         ]),
         "touch",
     );
 
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take").arg(".");
+    cmd.arg("take").arg("widget");
     cmd.assert().success();
 
-    let file1_path = Path::new("file1");
-    let file2_path = Path::new("file2");
-    assert!(file1_path.is_file());
-    assert!(file2_path.is_file());
+    let mut contents = String::new();
+    fs::File::open("widget/widget.rs")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "mod widget;");
+    assert!(Path::new("widget/widget_test.rs").is_file());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_placeholder_names_conflict() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let _t = Test::init(
+        "take_from_dir_placeholder_names_conflict",
+        vec![templ_dir.clone()],
+        HashMap::from([
+            (templ_dir.join("{{name}}.rs"), String::new()),
+            (templ_dir.join("other.rs"), String::new()),
+            (PathBuf::from_str("widget.rs")?, String::new()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg(".").arg("--var").arg("name=widget");
+    cmd.assert().failure();
+    assert!(!Path::new("other.rs").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_from_dir_placeholder_names_unexpanded() -> Result<(), Box<dyn Error>> {
+    let templ_dir = PathBuf::from_str(".templ.aar")?;
+    let _t = Test::init(
+        "take_from_dir_placeholder_names_unexpanded",
+        vec![templ_dir.clone()],
+        HashMap::from([(templ_dir.join("{{name}}.rs"), String::new())]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("widget").arg("--raw");
+    cmd.assert().failure();
+    assert!(!Path::new("widget").join("{{name}}.rs").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_cursor() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_cursor",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "# Title\n\n{{cursor}}\n{{cursor}}end\n".to_string(),
+        )]),
+        // Fake editor which stores its arguments
+        "sh -c 'echo \"$@\" > args' sh",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("dir/file")
+        .env("TEMPLAAR_CURSOR_LINE", "1")
+        .write_stdin("y");
+    fs::create_dir("dir")?;
+    cmd.assert().success();
 
     let mut contents = String::new();
-    fs::File::open(&file1_path)?.read_to_string(&mut contents)?;
-    assert_eq!(contents, file1_content);
+    fs::File::open("dir/file")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "# Title\n\n\nend\n");
 
     contents.clear();
-    fs::File::open(&file2_path)?.read_to_string(&mut contents)?;
-    assert_eq!(contents, file2_content);
+    fs::File::open("args")?.read_to_string(&mut contents)?;
+    assert_eq!(
+        contents,
+        format!("+3 {}\n", env::current_dir()?.join("dir/file").display())
+    );
 
     Ok(())
 }
 
 #[test]
 #[serial]
-fn test_take_from_dir_conflict() -> Result<(), Box<dyn Error>> {
-    let templ_dir = PathBuf::from_str(".templ.aar")?;
-    let file1_content = "Template";
-    let file2_content = "Other template";
+fn test_take_cursor_unsupported() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_cursor_unsupported",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "# Title\n{{cursor}}\n".to_string(),
+        )]),
+        "sh -c 'echo \"$@\" > args' sh",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .env("TEMPLAAR_CURSOR_LINE", "0")
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("args")?.read_to_string(&mut contents)?;
+    assert_eq!(
+        contents,
+        format!("{}\n", env::current_dir()?.join("templ").display())
+    );
 
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_custom_ext() -> Result<(), Box<dyn Error>> {
+    let templ_content = "Template";
     let _t = Test::init(
-        "take_from_dir",
-        vec![templ_dir.clone()],
+        "take_custom_ext",
+        vec![],
         HashMap::from([
-            (templ_dir.join("file1"), file1_content.to_string()),
-            (templ_dir.join("file2"), file2_content.to_string()),
-            (PathBuf::from_str("file1")?, String::new()),
+            (PathBuf::from_str(".templ.tmpl")?, templ_content.to_string()),
+            (PathBuf::from_str(".other.aar")?, String::new()),
         ]),
         "touch",
     );
 
     let mut cmd = Command::cargo_bin("templaar")?;
-    cmd.arg("take").arg(".");
-    cmd.assert().failure();
+    cmd.arg("take").env("TEMPLAAR_EXT", "tmpl").write_stdin("y");
+    cmd.assert().success();
 
-    assert!(!Path::new("file2").exists());
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, templ_content);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_config_default_template() -> Result<(), Box<dyn Error>> {
+    let config_dir = Path::new("config").join("templaar");
+    let _t = Test::init(
+        "take_config_default_template",
+        vec![config_dir.to_path_buf()],
+        HashMap::from([
+            (
+                config_dir.join("config.toml"),
+                "default_template_name = \"note\"\n".to_string(),
+            ),
+            (PathBuf::from_str(".note.aar")?, "Note".to_string()),
+            (PathBuf::from_str(".other.aar")?, "Other".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .env("XDG_CONFIG_HOME", env::current_dir()?.join("config"))
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("note")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Note");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "tera")]
+fn test_take_engine_tera() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_engine_tera",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "---aar\n\
+             engine = \"tera\"\n\
+             ---\n\
+             {% for i in [1, 2] %}{{ author }}{{ i }} {% endfor %}"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take")
+        .arg("--var")
+        .arg("author=me")
+        .write_stdin("y");
+    cmd.assert().success();
+
+    let mut contents = String::new();
+    fs::File::open("templ")?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "me1 me2 ");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_engine_error() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_engine_error",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "---aar\n\
+             engine = \"tera\"\n\
+             ---\n\
+             {% for %}"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains(".templ.aar"));
+    assert!(!Path::new("templ").exists());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "tera")]
+fn test_take_engine_error_line() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_engine_error_line",
+        vec![],
+        HashMap::from([(
+            PathBuf::from_str(".templ.aar")?,
+            "---aar\n\
+             engine = \"tera\"\n\
+             ---\n\
+             {{ name }}\n\
+             {% for %}"
+                .to_string(),
+        )]),
+        "touch",
+    );
+
+    // Line numbers are counted from the start of the template file
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take");
+    let output = cmd.output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(" --> 5:8"));
+    assert!(stderr.contains("5 | {% for %}"));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_inner_ext() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_inner_ext",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".report.md.aar")?, "report".to_string()),
+            (PathBuf::from_str(".notes.aar")?, "notes".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("list").arg("--names-only");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "notes\nreport\n");
+
+    // The default target keeps the inner extension
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("report");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("report.md")?, "report");
+
+    // The full name selects the template, too
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("report.md").arg("other.md");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("other.md")?, "report");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("notes");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("notes")?, "notes");
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_take_inner_ext_full_name_preferred() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "take_inner_ext_full_name_preferred",
+        vec![],
+        HashMap::from([
+            (PathBuf::from_str(".report.aar")?, "plain".to_string()),
+            (PathBuf::from_str(".report.md.aar")?, "markdown".to_string()),
+        ]),
+        "touch",
+    );
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("report");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("report")?, "plain");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("take").arg("-t").arg("report.md");
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string("report.md")?, "markdown");
 
     Ok(())
 }
@@ -39,6 +39,15 @@ impl Test {
         fs::create_dir(&test_dir)?;
         let cwd = env::current_dir()?;
         env::set_current_dir(&test_dir)?;
+        // Global templates are located using $HOME in tests, which points to
+        // the test directory unless a test sets it otherwise (so that the
+        // templates of the user running the tests are never found)
+        env::remove_var("XDG_CONFIG_HOME");
+        env::set_var("HOME", env::current_dir()?);
+        // Answers to queries are piped to stdin
+        env::set_var("TEMPLAAR_INTERACTIVE", "1");
+        // Do not remember used templates outside of the test directory
+        env::set_var("XDG_STATE_HOME", env::current_dir()?.join(".state"));
 
         // Create directories
         for dir in &init_dirs {
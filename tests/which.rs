@@ -0,0 +1,74 @@
+mod utils;
+
+use assert_cmd::Command;
+use serial_test::serial;
+use std::{collections::HashMap, env, error::Error, path::Path};
+use utils::Test;
+
+#[test]
+#[serial]
+fn test_which_parent() -> Result<(), Box<dyn Error>> {
+    let subdir = Path::new("testdir");
+    let _t = Test::init(
+        "which_parent",
+        vec![subdir.to_path_buf()],
+        HashMap::from([(Path::new(".templ.aar").to_path_buf(), String::new())]),
+        "touch",
+    );
+    let templ_path = env::current_dir()?.join(".templ.aar");
+
+    env::set_current_dir(subdir)?;
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("which");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!("{}\n", templ_path.display())
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_which_not_found() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init("which_not_found", vec![], HashMap::new(), "touch");
+    env::set_var("HOME", env::current_dir()?.join("home"));
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("which").arg("-t").arg("missing");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_which_ignore_case() -> Result<(), Box<dyn Error>> {
+    let _t = Test::init(
+        "which_ignore_case",
+        vec![],
+        HashMap::from([(Path::new(".license.aar").to_path_buf(), String::new())]),
+        "touch",
+    );
+    let templ_path = env::current_dir()?.join(".license.aar");
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("which").arg("-t").arg("License");
+    cmd.assert().failure();
+
+    let mut cmd = Command::cargo_bin("templaar")?;
+    cmd.arg("which")
+        .arg("-t")
+        .arg("License")
+        .arg("--ignore-case");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!("{}\n", templ_path.display())
+    );
+
+    Ok(())
+}